@@ -0,0 +1,318 @@
+use std::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+};
+
+/// Storage for the bits of a [`BitSet`]: a `Vec<u64>` grows as needed while
+/// a `[u64; N]` holds a fixed `64 * N` bits.
+pub trait Words: AsRef<[u64]> + AsMut<[u64]> + Clone {
+    fn empty() -> Self;
+
+    /// Makes room for at least `words` words.
+    fn ensure_words(&mut self, words: usize);
+}
+
+impl Words for Vec<u64> {
+    fn empty() -> Self {
+        Vec::new()
+    }
+
+    fn ensure_words(&mut self, words: usize) {
+        if self.len() < words {
+            self.resize(words, 0);
+        }
+    }
+}
+
+impl<const N: usize> Words for [u64; N] {
+    fn empty() -> Self {
+        [0; N]
+    }
+
+    fn ensure_words(&mut self, words: usize) {
+        assert!(
+            words <= N,
+            "bit {} doesn't fit in a {}-bit set",
+            words * 64 - 1,
+            N * 64
+        );
+    }
+}
+
+/// A set of small non-negative integers stored one bit each.
+#[derive(Clone)]
+pub struct BitSet<S = Vec<u64>> {
+    words: S,
+}
+
+#[allow(dead_code)]
+pub type FixedBitSet<const N: usize> = BitSet<[u64; N]>;
+
+impl<S: Words> BitSet<S> {
+    pub fn new() -> Self {
+        Self { words: S::empty() }
+    }
+
+    fn words(&self) -> &[u64] {
+        self.words.as_ref()
+    }
+
+    // the words up to and including the last non-zero one
+    fn significant_words(&self) -> &[u64] {
+        let words = self.words();
+        let len = words.iter().rposition(|w| *w != 0).map_or(0, |i| i + 1);
+        &words[..len]
+    }
+
+    pub fn contains(&self, bit: usize) -> bool {
+        self.words()
+            .get(bit / 64)
+            .is_some_and(|w| w & (1 << (bit % 64)) != 0)
+    }
+
+    /// Adds `bit`, returning whether it was newly added.
+    pub fn insert(&mut self, bit: usize) -> bool {
+        self.words.ensure_words(bit / 64 + 1);
+        let word = &mut self.words.as_mut()[bit / 64];
+        let mask = 1 << (bit % 64);
+        let added = *word & mask == 0;
+        *word |= mask;
+        added
+    }
+
+    /// Removes `bit`, returning whether it was present.
+    pub fn remove(&mut self, bit: usize) -> bool {
+        let present = self.contains(bit);
+        if present {
+            self.words.as_mut()[bit / 64] &= !(1 << (bit % 64));
+        }
+        present
+    }
+
+    /// A copy of this set with `bit` added.
+    pub fn with(&self, bit: usize) -> Self {
+        let mut set = self.clone();
+        set.insert(bit);
+        set
+    }
+
+    pub fn clear(&mut self) {
+        self.words.as_mut().iter_mut().for_each(|w| *w = 0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.words().iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words().iter().all(|w| *w == 0)
+    }
+
+    pub fn iter(&self) -> Ones<'_> {
+        Ones {
+            words: self.words(),
+            index: 0,
+            current: self.words().first().copied().unwrap_or(0),
+        }
+    }
+
+    fn combine(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        let (a, b) = (self.words(), other.words());
+        let len = a.len().max(b.len());
+        let mut words = S::empty();
+        words.ensure_words(len);
+        for (i, w) in words.as_mut().iter_mut().enumerate().take(len) {
+            *w = f(
+                a.get(i).copied().unwrap_or(0),
+                b.get(i).copied().unwrap_or(0),
+            );
+        }
+        Self { words }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a | b)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a & b)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words()
+            .iter()
+            .zip(other.words())
+            .all(|(a, b)| a & b == 0)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words()
+            .iter()
+            .enumerate()
+            .all(|(i, a)| a & !other.words().get(i).copied().unwrap_or(0) == 0)
+    }
+
+    /// Every subset of this set, starting with the empty set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set has 64 or more members.
+    pub fn subsets(&self) -> impl Iterator<Item = Self> {
+        let members = self.iter().collect::<Vec<usize>>();
+        assert!(members.len() < 64, "too many members to enumerate subsets");
+        (0..1u64 << members.len()).map(move |mask| {
+            members
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, bit)| *bit)
+                .collect()
+        })
+    }
+}
+
+impl<S: Words> Default for BitSet<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// trailing zero words don't change which bits are set, so they are ignored
+// when comparing or hashing growable sets
+impl<S: Words> PartialEq for BitSet<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.significant_words() == other.significant_words()
+    }
+}
+
+impl<S: Words> Eq for BitSet<S> {}
+
+impl<S: Words> Hash for BitSet<S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.significant_words().hash(state);
+    }
+}
+
+impl<S: Words> Debug for BitSet<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<S: Words> FromIterator<usize> for BitSet<S> {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<S: Words> Extend<usize> for BitSet<S> {
+    fn extend<T: IntoIterator<Item = usize>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|bit| {
+            self.insert(bit);
+        });
+    }
+}
+
+impl<'a, S: Words> IntoIterator for &'a BitSet<S> {
+    type Item = usize;
+    type IntoIter = Ones<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterates the set bits of a [`BitSet`] in ascending order.
+pub struct Ones<'a> {
+    words: &'a [u64],
+    index: usize,
+    current: u64,
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current == 0 {
+            self.index += 1;
+            self.current = *self.words.get(self.index)?;
+        }
+
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some(self.index * 64 + bit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set: BitSet = BitSet::new();
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(130));
+        assert!(set.contains(130) && !set.contains(129));
+        assert_eq!(set.iter().collect::<Vec<usize>>(), vec![3, 130]);
+        assert!(set.remove(130));
+        assert_eq!(set.len(), 1);
+        assert_eq!(set, [3].into_iter().collect());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: FixedBitSet<2> = [1, 2, 70].into_iter().collect();
+        let b: FixedBitSet<2> = [2, 3].into_iter().collect();
+        assert_eq!(
+            a.union(&b).iter().collect::<Vec<usize>>(),
+            vec![1, 2, 3, 70]
+        );
+        assert_eq!(a.intersection(&b).iter().collect::<Vec<usize>>(), vec![2]);
+        assert_eq!(a.difference(&b).iter().collect::<Vec<usize>>(), vec![1, 70]);
+        assert!(!a.is_disjoint(&b));
+        assert!(a.intersection(&b).is_subset(&b));
+    }
+
+    #[test]
+    fn test_subsets() {
+        let set: BitSet = [0, 65, 100].into_iter().collect();
+        let subsets = set.subsets().collect::<Vec<BitSet>>();
+        assert_eq!(subsets.len(), 8);
+        assert!(subsets[0].is_empty());
+        assert!(subsets.iter().all(|s| s.is_subset(&set)));
+    }
+
+    #[test]
+    fn test_hash_key() {
+        let mut a: BitSet = BitSet::new();
+        a.insert(200);
+        a.remove(200);
+        a.insert(5);
+        let b: BitSet = [5].into_iter().collect();
+
+        let mut memo = HashMap::new();
+        memo.insert(a, 1);
+        assert_eq!(memo.get(&b), Some(&1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fixed_overflow() {
+        let mut set: FixedBitSet<1> = BitSet::new();
+        set.insert(64);
+    }
+}
@@ -0,0 +1,231 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A cycle in a sequence of states: state `start + length` is the same as
+/// state `start`, and it repeats forever after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: u64,
+    pub length: u64,
+}
+
+impl Cycle {
+    /// Maps `target` onto the step in the first repetition that shares its
+    /// state.
+    pub fn equivalent_step(&self, target: u64) -> u64 {
+        if target < self.start {
+            target
+        } else {
+            self.start + (target - self.start) % self.length
+        }
+    }
+}
+
+/// Floyd's tortoise and hare over the states produced by repeatedly applying
+/// `step` to `initial`.
+#[allow(dead_code)]
+pub fn floyd<T: PartialEq>(initial: T, step: impl Fn(&T) -> T) -> Cycle {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Brent's algorithm, which finds the same cycle as [`floyd`] with fewer
+/// calls to `step`.
+#[allow(dead_code)]
+pub fn brent<T: PartialEq + Clone>(initial: T, step: impl Fn(&T) -> T) -> Cycle {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Detects cycles in simulations whose full state is too big to compare,
+/// using a smaller key that summarises it. A repeated key only proposes a
+/// cycle; it is accepted once the metric has grown by the same amounts over
+/// two whole periods, so a key that is too coarse can't produce a bogus
+/// answer.
+#[derive(Debug)]
+pub struct CycleDetector<K> {
+    first_step: u64,
+    seen: HashMap<K, Vec<u64>>,
+    metrics: Vec<i64>,
+}
+
+impl<K: Hash + Eq> CycleDetector<K> {
+    pub fn new() -> Self {
+        Self {
+            first_step: 0,
+            seen: HashMap::new(),
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Records the state `key` and `metric` reached at `step`. Steps must be
+    /// recorded one after another without gaps.
+    pub fn record(&mut self, step: u64, key: K, metric: i64) -> Option<Cycle> {
+        if self.metrics.is_empty() {
+            self.first_step = step;
+        }
+        assert_eq!(
+            step,
+            self.first_step + self.metrics.len() as u64,
+            "steps must be recorded in order"
+        );
+
+        let index = self.metrics.len();
+        self.metrics.push(metric);
+
+        let previous = self.seen.entry(key).or_default();
+        let cycle = previous.iter().rev().find_map(|&prev| {
+            let prev = prev as usize;
+            let length = index - prev;
+            if prev < length {
+                return None;
+            }
+
+            let start = prev - length;
+            let growth = self.metrics[prev] - self.metrics[start];
+            (start..=prev)
+                .all(|i| self.metrics[i + length] - self.metrics[i] == growth)
+                .then_some(Cycle {
+                    start: self.first_step + start as u64,
+                    length: length as u64,
+                })
+        });
+        previous.push(index as u64);
+
+        cycle
+    }
+
+    /// The metric at `target`, extrapolated from `cycle` if it hasn't been
+    /// recorded yet.
+    pub fn extrapolate(&self, cycle: Cycle, target: u64) -> i64 {
+        let index = |step: u64| (step - self.first_step) as usize;
+        if let Some(metric) = self.metrics.get(index(target)) {
+            return *metric;
+        }
+
+        let growth =
+            self.metrics[index(cycle.start + cycle.length)] - self.metrics[index(cycle.start)];
+        let repeats = ((target - cycle.start) / cycle.length) as i64;
+        self.metrics[index(cycle.equivalent_step(target))] + growth * repeats
+    }
+}
+
+impl<K: Hash + Eq> Default for CycleDetector<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 0, 1, 2, 3, 4, 5, 6, 3, 4, ...
+    fn step(n: &u64) -> u64 {
+        if *n == 6 {
+            3
+        } else {
+            n + 1
+        }
+    }
+
+    #[test]
+    fn test_floyd() {
+        assert_eq!(
+            floyd(0, step),
+            Cycle {
+                start: 3,
+                length: 4
+            }
+        );
+        assert_eq!(
+            floyd(5, step),
+            Cycle {
+                start: 0,
+                length: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(
+            brent(0, step),
+            Cycle {
+                start: 3,
+                length: 4
+            }
+        );
+        assert_eq!(
+            brent(5u64, |n| (n * n + 1) % 255),
+            floyd(5u64, |n| (n * n + 1) % 255)
+        );
+    }
+
+    #[test]
+    fn test_detector() {
+        // key repeats every 2 steps but the metric only every 4
+        let growth = [3, 1, 4, 1];
+        let mut detector = CycleDetector::new();
+        let mut metric = 0;
+        let mut found = None;
+        for step in 0..100 {
+            if let Some(cycle) = detector.record(step, step % 2, metric) {
+                found = Some(cycle);
+                break;
+            }
+            metric += growth[step as usize % 4];
+        }
+
+        let cycle = found.unwrap();
+        assert_eq!(cycle.length, 4);
+        assert_eq!(detector.extrapolate(cycle, 1_000), 9 * 250);
+        assert_eq!(detector.extrapolate(cycle, 1_001), 9 * 250 + 3);
+    }
+}
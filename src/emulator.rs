@@ -0,0 +1,251 @@
+use std::fmt::Display;
+
+use crate::parse::{complete, literal, preceded, signed, Input, ParseError};
+
+/// One of the registers `a` to `z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Register(u8);
+
+impl Register {
+    pub const COUNT: usize = 26;
+
+    pub fn new(name: char) -> Option<Self> {
+        name.is_ascii_lowercase().then(|| Self(name as u8 - b'a'))
+    }
+
+    pub fn name(self) -> char {
+        (b'a' + self.0) as char
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Noop,
+    /// `add<register> <value>`, as in `addx 3`.
+    Add(Register, i64),
+}
+
+impl Instruction {
+    pub fn decode(input: Input) -> Result<Self, ParseError> {
+        if input.as_str() == "noop" {
+            return Ok(Self::Noop);
+        }
+
+        let register = input
+            .as_str()
+            .strip_prefix("add")
+            .and_then(|rest| rest.chars().next())
+            .and_then(Register::new)
+            .ok_or_else(|| input.error("expected `noop` or `add<register> <value>`"))?;
+        // past `add` and the register name
+        let value = complete(preceded(literal(" "), signed()), input.advance(4))?;
+        Ok(Self::Add(register, value))
+    }
+
+    /// How many cycles the instruction takes; its effect lands at the end
+    /// of the last one.
+    pub fn cycles(&self) -> usize {
+        match self {
+            Instruction::Noop => 1,
+            Instruction::Add(..) => 2,
+        }
+    }
+
+    fn execute(&self, registers: &mut [i64; Register::COUNT]) {
+        match self {
+            Instruction::Noop => {}
+            Instruction::Add(r, value) => registers[r.0 as usize] += value,
+        }
+    }
+
+    fn register(&self) -> Option<Register> {
+        match self {
+            Instruction::Noop => None,
+            Instruction::Add(r, _) => Some(*r),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Add(r, value) => write!(f, "add{} {value}", r.name()),
+        }
+    }
+}
+
+/// The machine as seen during one cycle, before the instruction being run
+/// has taken effect.
+pub struct Tick<'c> {
+    /// Counting from 1.
+    pub cycle: usize,
+    pub instruction: Instruction,
+    registers: &'c [i64; Register::COUNT],
+    // the registers worth printing in a trace
+    shown: &'c [Register],
+}
+
+impl Tick<'_> {
+    pub fn register(&self, register: Register) -> i64 {
+        self.registers[register.0 as usize]
+    }
+}
+
+impl Display for Tick<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>6}  {:<10}", self.cycle, self.instruction.to_string())?;
+        for r in self.shown {
+            write!(f, " {}={}", r.name(), self.register(*r))?;
+        }
+        Ok(())
+    }
+}
+
+type Hook<'a> = Box<dyn FnMut(&Tick) + 'a>;
+
+/// Runs a program cycle by cycle, calling every hook once per cycle.
+pub struct Cpu<'a> {
+    program: Vec<Instruction>,
+    registers: [i64; Register::COUNT],
+    shown: Vec<Register>,
+    hooks: Vec<Hook<'a>>,
+    trace: bool,
+    stop_at: Option<usize>,
+}
+
+impl<'a> Cpu<'a> {
+    pub fn new(program: Vec<Instruction>) -> Self {
+        let mut shown = program
+            .iter()
+            .filter_map(Instruction::register)
+            .collect::<Vec<Register>>();
+        shown.sort();
+        shown.dedup();
+
+        Self {
+            program,
+            registers: [0; Register::COUNT],
+            shown,
+            hooks: Vec::new(),
+            trace: false,
+            stop_at: None,
+        }
+    }
+
+    pub fn set_register(&mut self, register: Register, value: i64) {
+        self.registers[register.0 as usize] = value;
+        if let Err(i) = self.shown.binary_search(&register) {
+            self.shown.insert(i, register);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn register(&self, register: Register) -> i64 {
+        self.registers[register.0 as usize]
+    }
+
+    pub fn add_hook(&mut self, hook: impl FnMut(&Tick) + 'a) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Prints every cycle as it runs.
+    pub fn trace(&mut self) {
+        self.trace = true;
+    }
+
+    /// Stops once `cycle` has run, even part way through an instruction.
+    pub fn stop_at(&mut self, cycle: usize) {
+        self.stop_at = Some(cycle);
+    }
+
+    /// Runs the program to the end, or until the stop cycle, returning how
+    /// many cycles ran.
+    pub fn run(&mut self) -> usize {
+        let mut cycle = 0;
+        for instruction in &self.program {
+            for _ in 0..instruction.cycles() {
+                if self.stop_at.is_some_and(|stop| cycle >= stop) {
+                    return cycle;
+                }
+                cycle += 1;
+
+                let tick = Tick {
+                    cycle,
+                    instruction: *instruction,
+                    registers: &self.registers,
+                    shown: &self.shown,
+                };
+                if self.trace {
+                    println!("{tick}");
+                }
+                self.hooks.iter_mut().for_each(|hook| hook(&tick));
+            }
+            instruction.execute(&mut self.registers);
+        }
+        cycle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn program(source: &str) -> Vec<Instruction> {
+        Input::new(source)
+            .lines()
+            .map(Instruction::decode)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decode() {
+        let source = "noop\naddx 3\naddy -5";
+        let decoded = program(source);
+        let x = Register::new('x').unwrap();
+        assert_eq!(decoded[1], Instruction::Add(x, 3));
+        let printed = decoded.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        assert_eq!(printed.join("\n"), source);
+
+        assert!(Instruction::decode(Input::new("addX 3")).is_err());
+        assert!(Instruction::decode(Input::new("addx")).is_err());
+        assert_eq!(
+            Instruction::decode(Input::new("addx 1 2"))
+                .unwrap_err()
+                .column,
+            7
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let (x, y) = (Register::new('x').unwrap(), Register::new('y').unwrap());
+        let mut seen = Vec::new();
+        let mut cpu = Cpu::new(program("noop\naddx 3\naddy -5\naddx -5"));
+        cpu.set_register(x, 1);
+        cpu.add_hook(|tick| seen.push(tick.to_string()));
+        assert_eq!(cpu.run(), 7);
+        assert_eq!(cpu.register(x), -1);
+        assert_eq!(cpu.register(y), -5);
+        drop(cpu);
+
+        assert_eq!(seen[0], "     1  noop       x=1 y=0");
+        assert_eq!(seen[2], "     3  addx 3     x=1 y=0");
+        assert_eq!(seen[3], "     4  addy -5    x=4 y=0");
+        assert_eq!(seen[6], "     7  addx -5    x=4 y=-5");
+    }
+
+    #[test]
+    fn test_stop_at() {
+        let mut cycles = Vec::new();
+        let mut cpu = Cpu::new(program("addx 1\naddx 1\naddx 1"));
+        cpu.stop_at(3);
+        cpu.add_hook(|tick| cycles.push(tick.cycle));
+        assert_eq!(cpu.run(), 3);
+        // the instruction cut off part way never lands
+        assert_eq!(cpu.register(Register::new('x').unwrap()), 1);
+        drop(cpu);
+        assert_eq!(cycles, [1, 2, 3]);
+    }
+}
@@ -0,0 +1,326 @@
+use std::fmt::Display;
+
+use crate::math::Rational;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinOp {
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol {
+            "+" => Some(BinOp::Add),
+            "-" => Some(BinOp::Sub),
+            "*" => Some(BinOp::Mul),
+            "/" => Some(BinOp::Div),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            BinOp::Add => '+',
+            BinOp::Sub => '-',
+            BinOp::Mul => '*',
+            BinOp::Div => '/',
+        }
+    }
+
+    pub fn apply(&self, left: Rational, right: Rational) -> Result<Rational, ExprError> {
+        match self {
+            BinOp::Add => Ok(left + right),
+            BinOp::Sub => Ok(left - right),
+            BinOp::Mul => Ok(left * right),
+            BinOp::Div => left.checked_div(right).ok_or(ExprError::DivisionByZero),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExprError {
+    DivisionByZero,
+    /// The expression depends on the unknown, so it has no single value.
+    Unknown,
+    /// The unknown ends up multiplied by itself or in a divisor.
+    NonLinear,
+    NoSolution,
+    /// Every value of the unknown satisfies the equation.
+    AnySolution,
+}
+
+impl Display for ExprError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            ExprError::DivisionByZero => "division by zero",
+            ExprError::Unknown => "expression depends on the unknown",
+            ExprError::NonLinear => "equation isn't linear in the unknown",
+            ExprError::NoSolution => "equation has no solution",
+            ExprError::AnySolution => "every value solves the equation",
+        };
+        write!(f, "{message}")
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Value(Rational),
+    Unknown,
+    Binary {
+        op: BinOp,
+        left: NodeId,
+        right: NodeId,
+    },
+}
+
+/// An arithmetic expression over a single unknown, stored as an arena of
+/// nodes. Children are always pushed before their parents, so every pass
+/// over the tree can run in index order.
+#[derive(Debug, Clone, Default)]
+pub struct ExprTree {
+    nodes: Vec<Node>,
+    unknown_name: String,
+}
+
+impl ExprTree {
+    pub fn new(unknown_name: &str) -> Self {
+        Self {
+            nodes: Vec::new(),
+            unknown_name: unknown_name.to_string(),
+        }
+    }
+
+    pub fn value(&mut self, value: impl Into<Rational>) -> NodeId {
+        self.push(Node::Value(value.into()))
+    }
+
+    pub fn unknown(&mut self) -> NodeId {
+        self.push(Node::Unknown)
+    }
+
+    pub fn binary(&mut self, op: BinOp, left: NodeId, right: NodeId) -> NodeId {
+        assert!(
+            left < self.nodes.len() && right < self.nodes.len(),
+            "operands must be added before the operation"
+        );
+        self.push(Node::Binary { op, left, right })
+    }
+
+    fn push(&mut self, node: Node) -> NodeId {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn evaluate(&self, id: NodeId) -> Result<Rational, ExprError> {
+        match self.nodes[id] {
+            Node::Value(value) => Ok(value),
+            Node::Unknown => Err(ExprError::Unknown),
+            Node::Binary { op, left, right } => {
+                op.apply(self.evaluate(left)?, self.evaluate(right)?)
+            }
+        }
+    }
+
+    /// Marks every node with the unknown somewhere beneath it.
+    pub fn dependent_nodes(&self) -> Vec<bool> {
+        let mut dependent = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let marked = match node {
+                Node::Value(_) => false,
+                Node::Unknown => true,
+                Node::Binary { left, right, .. } => dependent[*left] || dependent[*right],
+            };
+            dependent.push(marked);
+        }
+        dependent
+    }
+
+    pub fn equation(&self, lhs: NodeId, rhs: NodeId) -> Equation<'_> {
+        Equation {
+            tree: self,
+            lhs,
+            rhs,
+            dependent: self.dependent_nodes(),
+        }
+    }
+}
+
+/// `a * unknown + b`
+#[derive(Debug, Clone, Copy)]
+struct Linear {
+    a: Rational,
+    b: Rational,
+}
+
+impl Linear {
+    fn combine(op: BinOp, l: Linear, r: Linear) -> Result<Linear, ExprError> {
+        match op {
+            BinOp::Add => Ok(Linear {
+                a: l.a + r.a,
+                b: l.b + r.b,
+            }),
+            BinOp::Sub => Ok(Linear {
+                a: l.a - r.a,
+                b: l.b - r.b,
+            }),
+            BinOp::Mul if !l.a.is_zero() && !r.a.is_zero() => Err(ExprError::NonLinear),
+            BinOp::Mul => Ok(Linear {
+                a: l.a * r.b + r.a * l.b,
+                b: l.b * r.b,
+            }),
+            BinOp::Div if !r.a.is_zero() => Err(ExprError::NonLinear),
+            BinOp::Div => Ok(Linear {
+                a: BinOp::Div.apply(l.a, r.b)?,
+                b: BinOp::Div.apply(l.b, r.b)?,
+            }),
+        }
+    }
+}
+
+/// `lhs = rhs` over the nodes of an [`ExprTree`].
+pub struct Equation<'a> {
+    tree: &'a ExprTree,
+    lhs: NodeId,
+    rhs: NodeId,
+    dependent: Vec<bool>,
+}
+
+impl Equation<'_> {
+    fn linear(&self, id: NodeId) -> Result<Linear, ExprError> {
+        if !self.dependent[id] {
+            return Ok(Linear {
+                a: Rational::ZERO,
+                b: self.tree.evaluate(id)?,
+            });
+        }
+
+        match *self.tree.node(id) {
+            Node::Unknown => Ok(Linear {
+                a: Rational::ONE,
+                b: Rational::ZERO,
+            }),
+            Node::Binary { op, left, right } => {
+                Linear::combine(op, self.linear(left)?, self.linear(right)?)
+            }
+            Node::Value(_) => unreachable!("constant nodes aren't dependent"),
+        }
+    }
+
+    /// The value of the unknown that makes both sides equal. The unknown may
+    /// appear on either side, or both.
+    pub fn solve(&self) -> Result<Rational, ExprError> {
+        let diff = Linear::combine(BinOp::Sub, self.linear(self.lhs)?, self.linear(self.rhs)?)?;
+        if diff.a.is_zero() {
+            Err(if diff.b.is_zero() {
+                ExprError::AnySolution
+            } else {
+                ExprError::NoSolution
+            })
+        } else {
+            Ok(-diff.b / diff.a)
+        }
+    }
+
+    // constant subtrees are folded into their value
+    fn write_reduced(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        id: NodeId,
+        outermost: bool,
+    ) -> std::fmt::Result {
+        if !self.dependent[id] {
+            if let Ok(value) = self.tree.evaluate(id) {
+                return write!(f, "{value}");
+            }
+        }
+
+        match *self.tree.node(id) {
+            Node::Value(value) => write!(f, "{value}"),
+            Node::Unknown => write!(f, "{}", self.tree.unknown_name),
+            Node::Binary { op, left, right } => {
+                if !outermost {
+                    write!(f, "(")?;
+                }
+                self.write_reduced(f, left, false)?;
+                write!(f, " {} ", op.symbol())?;
+                self.write_reduced(f, right, false)?;
+                if !outermost {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for Equation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_reduced(f, self.lhs, true)?;
+        write!(f, " = ")?;
+        self.write_reduced(f, self.rhs, true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let mut tree = ExprTree::new("x");
+        let a = tree.value(7i64);
+        let b = tree.value(2i64);
+        let div = tree.binary(BinOp::Div, a, b);
+        assert_eq!(tree.evaluate(div), Ok(Rational::new(7, 2)));
+
+        let zero = tree.value(0i64);
+        let bad = tree.binary(BinOp::Div, div, zero);
+        assert_eq!(tree.evaluate(bad), Err(ExprError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_unknown_on_both_sides() {
+        // 2 * (x + 3) = x / 2 + 9
+        let mut tree = ExprTree::new("x");
+        let x = tree.unknown();
+        let (two, three, nine) = (tree.value(2i64), tree.value(3i64), tree.value(9i64));
+        let sum = tree.binary(BinOp::Add, x, three);
+        let lhs = tree.binary(BinOp::Mul, two, sum);
+        let half = tree.binary(BinOp::Div, x, two);
+        let rhs = tree.binary(BinOp::Add, half, nine);
+
+        let equation = tree.equation(lhs, rhs);
+        assert_eq!(equation.solve(), Ok(Rational::from(2i64)));
+        assert_eq!(equation.to_string(), "2 * (x + 3) = (x / 2) + 9");
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let mut tree = ExprTree::new("x");
+        let x = tree.unknown();
+        let square = tree.binary(BinOp::Mul, x, x);
+        let one = tree.value(1i64);
+        assert_eq!(
+            tree.equation(square, one).solve(),
+            Err(ExprError::NonLinear)
+        );
+
+        let shifted = tree.binary(BinOp::Add, x, one);
+        assert_eq!(
+            tree.equation(shifted, x).solve(),
+            Err(ExprError::NoSolution)
+        );
+        assert_eq!(tree.equation(x, x).solve(), Err(ExprError::AnySolution));
+    }
+}
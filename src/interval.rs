@@ -0,0 +1,256 @@
+use std::fmt::Display;
+
+/// A range of integers, stored half-open as `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval {
+    start: i64,
+    end: i64,
+}
+
+impl Interval {
+    /// `start..end`, excluding `end`.
+    pub fn exclusive(start: i64, end: i64) -> Self {
+        Self {
+            start,
+            end: end.max(start),
+        }
+    }
+
+    /// `start..=end`, including `end`. `None` if `end` is `i64::MAX`, as
+    /// there's no value past it to end on.
+    pub fn inclusive(start: i64, end: i64) -> Option<Self> {
+        Some(Self::exclusive(start, end.checked_add(1)?))
+    }
+
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+
+    /// The first value past the end of the interval.
+    pub fn end(&self) -> i64 {
+        self.end
+    }
+
+    /// The last value in the interval.
+    pub fn last(&self) -> i64 {
+        self.end - 1
+    }
+
+    pub fn len(&self) -> u64 {
+        self.start.abs_diff(self.end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        (self.start..self.end).contains(&value)
+    }
+
+    pub fn contains_interval(&self, other: &Interval) -> bool {
+        other.is_empty() || (self.start <= other.start && other.end <= self.end)
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        self.overlaps(other)
+            .then(|| Interval::exclusive(self.start.max(other.start), self.end.min(other.end)))
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// A set of integers kept as sorted, disjoint, non-touching intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeSet {
+    intervals: Vec<Interval>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Interval> {
+        self.intervals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Total number of values covered.
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.intervals.partition_point(|r| r.end <= value);
+        self.intervals.get(i).is_some_and(|r| r.contains(value))
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+
+        // everything touching `interval` gets merged into it
+        let from = self.intervals.partition_point(|r| r.end < interval.start);
+        let to = self.intervals.partition_point(|r| r.start <= interval.end);
+        let merged = self.intervals[from..to]
+            .iter()
+            .fold(interval, |acc, r| Interval {
+                start: acc.start.min(r.start),
+                end: acc.end.max(r.end),
+            });
+        self.intervals.splice(from..to, [merged]);
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        self.iter().chain(other.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut result = Vec::new();
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            if let Some(both) = x.intersection(y) {
+                result.push(both);
+            }
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        RangeSet { intervals: result }
+    }
+
+    pub fn subtract(&self, other: &RangeSet) -> RangeSet {
+        let mut result = Vec::new();
+        for interval in self.iter() {
+            let mut start = interval.start;
+            let from = other.intervals.partition_point(|r| r.end <= interval.start);
+            for cut in other.intervals[from..]
+                .iter()
+                .take_while(|r| r.start < interval.end)
+            {
+                if cut.start > start {
+                    result.push(Interval::exclusive(start, cut.start));
+                }
+                start = start.max(cut.end);
+            }
+            if start < interval.end {
+                result.push(Interval::exclusive(start, interval.end));
+            }
+        }
+
+        RangeSet { intervals: result }
+    }
+
+    /// The parts of `within` that aren't covered by this set.
+    pub fn gaps(&self, within: Interval) -> RangeSet {
+        RangeSet::from_iter([within]).subtract(self)
+    }
+}
+
+impl FromIterator<Interval> for RangeSet {
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
+        let mut intervals = iter
+            .into_iter()
+            .filter(|r| !r.is_empty())
+            .collect::<Vec<Interval>>();
+        intervals.sort_by_key(|r| r.start);
+
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for r in intervals {
+            match merged.last_mut() {
+                Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+                _ => merged.push(r),
+            }
+        }
+
+        RangeSet { intervals: merged }
+    }
+}
+
+impl Extend<Interval> for RangeSet {
+    fn extend<T: IntoIterator<Item = Interval>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|r| self.insert(r));
+    }
+}
+
+impl<'a> IntoIterator for &'a RangeSet {
+    type Item = &'a Interval;
+    type IntoIter = std::slice::Iter<'a, Interval>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(intervals: &[(i64, i64)]) -> RangeSet {
+        intervals
+            .iter()
+            .map(|(a, b)| Interval::inclusive(*a, *b).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_interval() {
+        let a = Interval::inclusive(2, 8).unwrap();
+        let b = Interval::exclusive(3, 8);
+        assert_eq!(a.len(), 7);
+        assert!(a.contains_interval(&b));
+        assert!(!b.contains_interval(&a));
+        assert_eq!(a.intersection(&b), Some(b));
+        assert_eq!(b.intersection(&Interval::inclusive(8, 9).unwrap()), None);
+        assert_eq!(Interval::inclusive(0, i64::MAX), None);
+        assert_eq!(
+            Interval::inclusive(0, i64::MAX - 1).unwrap().last(),
+            i64::MAX - 1
+        );
+    }
+
+    #[test]
+    fn test_merging() {
+        let mut ranges = set(&[(5, 7), (0, 2), (3, 4)]);
+        assert_eq!(ranges, set(&[(0, 7)]));
+
+        ranges.insert(Interval::inclusive(10, 12).unwrap());
+        ranges.insert(Interval::inclusive(-3, -2).unwrap());
+        assert_eq!(ranges, set(&[(-3, -2), (0, 7), (10, 12)]));
+        assert_eq!(ranges.len(), 13);
+        assert!(ranges.contains(11) && !ranges.contains(8));
+
+        ranges.insert(Interval::inclusive(-1, 9).unwrap());
+        assert_eq!(ranges, set(&[(-3, 12)]));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25)]);
+        assert_eq!(a.union(&b), set(&[(0, 30)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25)]));
+        assert_eq!(a.subtract(&b), set(&[(0, 4), (26, 30)]));
+        assert_eq!(b.subtract(&a), set(&[(11, 19)]));
+        assert_eq!(
+            a.gaps(Interval::inclusive(-5, 35).unwrap()),
+            set(&[(-5, -1), (11, 19), (31, 35)])
+        );
+    }
+}
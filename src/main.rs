@@ -1,44 +1,90 @@
+use params::Params;
 use utils::print_time_results;
 
+mod bitset;
+mod cycle;
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod day_template;
+mod emulator;
+mod expr;
+mod interval;
+mod math;
+mod params;
+mod parse;
+mod search;
 mod solver;
 mod utils;
+// days keep their puzzle input below their tests
+#[allow(clippy::items_after_test_module)]
 mod year_2020;
+#[allow(clippy::items_after_test_module)]
 mod year_2021;
+#[allow(clippy::items_after_test_module)]
 mod year_2022;
 
 struct Args {
     day: usize,
     year: usize,
     runs: usize,
+    params: Params,
 }
 
-fn main() {
+// usage: [day [runs [year]]] [--set <year>.<day>.<name>=<value>]... [--config <file>]...
+fn parse_args() -> Result<Args, String> {
     let mut args = Args {
         day: 0, // 0 implies run all
         year: 2022,
         runs: 1,
+        params: Params::default(),
     };
 
-    let raw_args: Vec<String> = std::env::args().collect();
+    let mut positional = Vec::new();
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        match arg.as_str() {
+            "--set" => {
+                let assignment = raw_args.next().ok_or("--set needs a value")?;
+                args.params.add(&assignment).map_err(|e| e.to_string())?;
+            }
+            "--config" => {
+                let path = raw_args.next().ok_or("--config needs a file")?;
+                let text = std::fs::read_to_string(&path).map_err(|e| format!("{path}: {e}"))?;
+                args.params
+                    .add_config(&text)
+                    .map_err(|e| format!("{path}: {e}"))?;
+            }
+            _ => positional.push(arg),
+        }
+    }
 
-    if let Some(runs) = raw_args.get(2).and_then(|r| r.parse::<usize>().ok()) {
+    if let Some(runs) = positional.get(1).and_then(|r| r.parse::<usize>().ok()) {
         args.runs = runs;
     }
 
-    if let Some(year) = raw_args.get(3).and_then(|r| r.parse::<usize>().ok()) {
+    if let Some(year) = positional.get(2).and_then(|r| r.parse::<usize>().ok()) {
         args.year = year;
     }
 
-    if let Some(day) = raw_args.get(1).and_then(|r| r.parse::<usize>().ok()) {
+    if let Some(day) = positional.first().and_then(|r| r.parse::<usize>().ok()) {
         args.day = day;
     }
 
+    args.params.check().map_err(|e| e.to_string())?;
+    Ok(args)
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(2);
+    });
+
     if args.day == 0 {
-        let results = solver::run_all(args.year, args.runs);
+        let results = solver::run_all(args.year, args.runs, &args.params);
         print_time_results(results, args.runs)
     } else {
-        let result = solver::run(args.year, args.day, args.runs);
+        let result = solver::run(args.year, args.day, args.runs, &args.params);
         if let Some(result) = result {
             print_time_results(vec![result], args.runs)
         }
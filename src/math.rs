@@ -0,0 +1,487 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, or `None` if it doesn't fit in a `u64`.
+pub fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// # Panics
+///
+/// Panics if the result overflows a `u64`.
+pub fn lcm(a: u64, b: u64) -> u64 {
+    checked_lcm(a, b).unwrap_or_else(|| panic!("lcm({a}, {b}) overflows u64"))
+}
+
+#[allow(dead_code)]
+pub fn gcd_all(values: impl IntoIterator<Item = u64>) -> u64 {
+    values.into_iter().fold(0, gcd)
+}
+
+/// The lcm of every value, `1` for no values.
+pub fn lcm_all(values: impl IntoIterator<Item = u64>) -> u64 {
+    values.into_iter().fold(1, lcm)
+}
+
+#[allow(dead_code)]
+pub fn checked_lcm_all(values: impl IntoIterator<Item = u64>) -> Option<u64> {
+    values.into_iter().try_fold(1, checked_lcm)
+}
+
+#[allow(dead_code)]
+pub fn checked_product(values: impl IntoIterator<Item = u64>) -> Option<u64> {
+    values
+        .into_iter()
+        .try_fold(1u64, |acc, v| acc.checked_mul(v))
+}
+
+#[allow(dead_code)]
+pub fn checked_sum(values: impl IntoIterator<Item = u64>) -> Option<u64> {
+    values
+        .into_iter()
+        .try_fold(0u64, |acc, v| acc.checked_add(v))
+}
+
+/// `(a * b) % m` without overflowing.
+pub fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+#[allow(dead_code)]
+pub fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Returns `(g, x, y)` with `g = gcd(a, b)` and `a * x + b * y == g`. Any
+/// `i64` or `u64` inputs are safe from overflow.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The inverse of `a` modulo `m`, if `m` is positive and `a` and `m` are
+/// coprime.
+#[allow(dead_code)]
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    // the inverse is below m, so it fits back in an i64
+    wide_inverse(a.into(), m.into()).map(|x| x as i64)
+}
+
+fn wide_inverse(a: i128, m: i128) -> Option<i128> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Solves `x ≡ residue (mod modulus)` for every pair, returning the smallest
+/// non-negative `x` and the combined modulus. The moduli don't need to be
+/// coprime; `None` means a modulus isn't positive, the congruences
+/// contradict each other or the combined modulus doesn't fit in an `i64`.
+#[allow(dead_code)]
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    // the combined modulus stays below 2^63, so nothing here overflows i128
+    let (x, m) = congruences
+        .iter()
+        .try_fold((0i128, 1i128), |(x, m), &(residue, modulus)| {
+            if modulus <= 0 {
+                return None;
+            }
+            let modulus = i128::from(modulus);
+            let residue = i128::from(residue).rem_euclid(modulus);
+            let (g, p, _) = extended_gcd(m, modulus);
+            let diff = residue - x;
+            if diff % g != 0 {
+                return None;
+            }
+
+            let step = modulus / g;
+            let lcm = m * step;
+            if lcm > i64::MAX.into() {
+                return None;
+            }
+            let k = (diff / g * p).rem_euclid(step);
+            Some(((x + m * k).rem_euclid(lcm), lcm))
+        })?;
+    Some((x.try_into().ok()?, m.try_into().ok()?))
+}
+
+/// An integer modulo a modulus chosen at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(dead_code)]
+pub struct ModInt {
+    value: u64,
+    modulus: u64,
+}
+
+#[allow(dead_code)]
+impl ModInt {
+    pub fn new(value: i64, modulus: u64) -> Self {
+        assert!(modulus > 0, "modulus must be positive");
+        Self {
+            value: i128::from(value).rem_euclid(modulus.into()) as u64,
+            modulus,
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    pub fn pow(&self, exp: u64) -> Self {
+        Self {
+            value: pow_mod(self.value, exp, self.modulus),
+            ..*self
+        }
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        // the inverse is below the modulus, so it fits back in a u64
+        wide_inverse(self.value.into(), self.modulus.into()).map(|v| self.with_value(v as u64))
+    }
+
+    fn with_value(&self, value: u64) -> Self {
+        Self { value, ..*self }
+    }
+
+    fn check_modulus(&self, other: &Self) {
+        assert_eq!(
+            self.modulus, other.modulus,
+            "can't mix values with different moduli"
+        );
+    }
+}
+
+impl Display for ModInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (mod {})", self.value, self.modulus)
+    }
+}
+
+impl Add for ModInt {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.check_modulus(&rhs);
+        self.with_value(((self.value as u128 + rhs.value as u128) % self.modulus as u128) as u64)
+    }
+}
+
+impl Sub for ModInt {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for ModInt {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.check_modulus(&rhs);
+        self.with_value(mul_mod(self.value, rhs.value, self.modulus))
+    }
+}
+
+impl Neg for ModInt {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        self.with_value((self.modulus - self.value) % self.modulus)
+    }
+}
+
+impl Add<u64> for ModInt {
+    type Output = Self;
+    fn add(self, rhs: u64) -> Self::Output {
+        self + self.with_value(rhs % self.modulus)
+    }
+}
+
+impl Mul<u64> for ModInt {
+    type Output = Self;
+    fn mul(self, rhs: u64) -> Self::Output {
+        self * self.with_value(rhs % self.modulus)
+    }
+}
+
+impl AddAssign for ModInt {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for ModInt {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for ModInt {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+/// An exact fraction, kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: i128,
+    denom: i128,
+}
+
+fn gcd_i128(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a as i128
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { numer: 0, denom: 1 };
+    pub const ONE: Rational = Rational { numer: 1, denom: 1 };
+
+    /// # Panics
+    ///
+    /// Panics if `denom` is zero.
+    pub fn new(numer: i128, denom: i128) -> Self {
+        assert!(denom != 0, "rational with a zero denominator");
+        let g = gcd_i128(numer, denom).max(1) * denom.signum();
+        Self {
+            numer: numer / g,
+            denom: denom / g,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn numer(&self) -> i128 {
+        self.numer
+    }
+
+    #[allow(dead_code)]
+    pub fn denom(&self) -> i128 {
+        self.denom
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    #[allow(dead_code)]
+    pub fn to_integer(self) -> Option<i128> {
+        self.is_integer().then_some(self.numer)
+    }
+
+    pub fn recip(&self) -> Option<Self> {
+        (!self.is_zero()).then(|| Self::new(self.denom, self.numer))
+    }
+
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        rhs.recip().map(|r| self * r)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Self::new(value as i128, 1)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Self::new(value, 1)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.numer * other.denom).cmp(&(other.numer * self.denom))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        let g = gcd_i128(self.denom, rhs.denom);
+        Self::new(
+            self.numer * (rhs.denom / g) + rhs.numer * (self.denom / g),
+            self.denom / g * rhs.denom,
+        )
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        // cancel across first to keep the intermediate products small
+        let a = gcd_i128(self.numer, rhs.denom).max(1);
+        let b = gcd_i128(rhs.numer, self.denom).max(1);
+        Self::new(
+            (self.numer / a) * (rhs.numer / b),
+            (self.denom / b) * (rhs.denom / a),
+        )
+    }
+}
+
+/// # Panics
+///
+/// Panics when dividing by zero; see [`Rational::checked_div`].
+impl Div for Rational {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs).expect("division by zero")
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self {
+            numer: -self.numer,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd_all([12, 18, 27]), 3);
+        assert_eq!(lcm_all([4, 6, 10]), 60);
+        assert_eq!(lcm_all([23, 19, 13, 17]), 96577);
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(checked_product([u64::MAX, 2]), None);
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(4, 8), None);
+        assert_eq!(mod_inverse(3, 0), None);
+        let (g, x, y) = extended_gcd(u64::MAX.into(), i64::MAX.into());
+        assert_eq!(u64::MAX as i128 * x + i64::MAX as i128 * y, g);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(3, 4), (1, 6)]), Some((7, 12)));
+        assert_eq!(crt(&[(0, 4), (1, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(1, 0)]), None);
+        assert_eq!(crt(&[(1, -3)]), None);
+        let (a, b) = (3_000_000_000, 3_000_000_001);
+        assert_eq!(crt(&[(-1, a), (-1, b)]), Some((a * b - 1, a * b)));
+        assert_eq!(crt(&[(-1, i64::MAX), (0, 2)]), None);
+    }
+
+    #[test]
+    fn test_mod_int() {
+        let m = 1_000_000_007;
+        let a = ModInt::new(-5, m);
+        assert_eq!(a.value(), m - 5);
+        assert_eq!((a + ModInt::new(7, m)).value(), 2);
+        assert_eq!((a * a).value(), 25);
+        assert_eq!((ModInt::new(2, m).pow(m - 1)).value(), 1);
+        assert_eq!(
+            (ModInt::new(3, m) * ModInt::new(3, m).inverse().unwrap()).value(),
+            1
+        );
+        assert_eq!((ModInt::new(3, 7) - ModInt::new(5, 7)).value(), 5);
+
+        // moduli past i64::MAX
+        let m = u64::MAX - 58; // prime
+        let a = ModInt::new(-1, m);
+        assert_eq!(a.value(), m - 1);
+        assert_eq!((a * a).value(), 1);
+        assert_eq!(a.inverse(), Some(a));
+        assert_eq!(
+            (ModInt::new(2, m) * ModInt::new(2, m).inverse().unwrap()).value(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_rational() {
+        let half = Rational::new(2, 4);
+        assert_eq!(half, Rational::new(-1, -2));
+        assert_eq!((half + Rational::new(1, 3)).to_string(), "5/6");
+        assert_eq!((half * Rational::from(4i64)).to_integer(), Some(2));
+        assert_eq!(Rational::new(3, -6) - half, Rational::from(-1i64));
+        assert_eq!(half.checked_div(Rational::ZERO), None);
+        assert!(Rational::new(1, 3) < half);
+    }
+}
@@ -0,0 +1,234 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    parse::{Input, ParseError},
+    solver::{get_solver, Solver},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    Unknown { name: String },
+    UnknownDay { year: usize, day: usize },
+    Invalid { name: String, message: String },
+    Syntax(ParseError),
+}
+
+impl ParamError {
+    pub fn unknown(name: &str) -> Self {
+        ParamError::Unknown {
+            name: name.to_string(),
+        }
+    }
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamError::Unknown { name } => write!(f, "unknown parameter `{name}`"),
+            ParamError::UnknownDay { year, day } => write!(f, "no solver for {year} day {day}"),
+            ParamError::Invalid { name, message } => {
+                write!(f, "invalid value for `{name}`: {message}")
+            }
+            ParamError::Syntax(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// Parses `value` into `slot`, for days implementing [`Solver::set_param`].
+pub fn assign<T>(slot: &mut T, name: &str, value: &str) -> Result<(), ParamError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    *slot = value
+        .trim()
+        .parse()
+        .map_err(|e: T::Err| ParamError::Invalid {
+            name: name.to_string(),
+            message: e.to_string(),
+        })?;
+    Ok(())
+}
+
+/// Sets `slot` to the file named by `value` if it has one of `extensions`,
+/// for params naming a file to write output to.
+pub fn assign_file(
+    slot: &mut String,
+    name: &str,
+    value: &str,
+    extensions: &[&str],
+) -> Result<(), ParamError> {
+    let path = value.trim();
+    match path.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && extensions.contains(&ext) => {
+            *slot = path.to_string();
+            Ok(())
+        }
+        _ => Err(ParamError::Invalid {
+            name: name.to_string(),
+            message: format!("expected a .{} file", extensions.join(" or .")),
+        }),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Override {
+    year: usize,
+    day: usize,
+    name: String,
+    value: String,
+}
+
+/// Parameter overrides for any number of days, written as
+/// `<year>.<day>.<name>=<value>`, e.g. `2022.15.row=10`.
+#[derive(Debug, Default, Clone)]
+pub struct Params {
+    overrides: Vec<Override>,
+}
+
+impl Params {
+    /// Adds one `<year>.<day>.<name>=<value>` assignment.
+    pub fn add(&mut self, assignment: &str) -> Result<(), ParamError> {
+        self.add_line(Input::new(assignment))
+    }
+
+    fn add_line(&mut self, line: Input) -> Result<(), ParamError> {
+        let syntax = |message: &str| ParamError::Syntax(line.error(message));
+        let (key, value) = line
+            .as_str()
+            .split_once('=')
+            .ok_or_else(|| syntax("expected `<year>.<day>.<name>=<value>`"))?;
+        let mut parts = key.trim().splitn(3, '.');
+        let (Some(year), Some(day), Some(name)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(syntax("expected a key like `2022.15.row`"));
+        };
+
+        self.overrides.push(Override {
+            year: year.parse().map_err(|_| syntax("invalid year"))?,
+            day: day.parse().map_err(|_| syntax("invalid day"))?,
+            name: name.to_string(),
+            value: value.trim().to_string(),
+        });
+        Ok(())
+    }
+
+    /// Adds every assignment in a config file, one per line. Blank lines and
+    /// lines starting with `#` are skipped.
+    pub fn add_config(&mut self, text: &str) -> Result<(), ParamError> {
+        Input::new(text)
+            .lines()
+            .map(Input::trim)
+            .filter(|l| !l.is_empty() && !l.as_str().starts_with('#'))
+            .try_for_each(|l| self.add_line(l))
+    }
+
+    /// Applies the overrides meant for `year` and `day` to `solver`, in the
+    /// order they were added.
+    pub fn apply(
+        &self,
+        year: usize,
+        day: usize,
+        solver: &mut dyn Solver,
+    ) -> Result<(), ParamError> {
+        self.overrides
+            .iter()
+            .filter(|o| o.year == year && o.day == day)
+            .try_for_each(|o| solver.set_param(&o.name, &o.value))
+    }
+
+    /// Makes sure every override names a real day and a parameter it
+    /// accepts, so mistakes show up before anything runs.
+    pub fn check(&self) -> Result<(), ParamError> {
+        self.overrides.iter().try_for_each(|o| {
+            let mut solver = get_solver(o.year, o.day).ok_or(ParamError::UnknownDay {
+                year: o.year,
+                day: o.day,
+            })?;
+            solver.set_param(&o.name, &o.value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Counter {
+        limit: u32,
+    }
+
+    impl Solver for Counter {
+        fn get_input(&self) -> &'static str {
+            ""
+        }
+
+        fn with_input(&mut self, _input: &str) {}
+
+        fn solve_part1(&self) -> String {
+            self.limit.to_string()
+        }
+
+        fn solve_part2(&self) -> String {
+            "".to_string()
+        }
+
+        fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+            match name {
+                "limit" => assign(&mut self.limit, name, value),
+                _ => Err(ParamError::unknown(name)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let mut params = Params::default();
+        params
+            .add_config("# comment\n2022.7.limit = 5\n\n2022.8.limit=9\n")
+            .unwrap();
+        params.add("2022.7.limit=6").unwrap();
+
+        let mut counter = Counter::default();
+        params.apply(2022, 7, &mut counter).unwrap();
+        assert_eq!(counter.solve_part1(), "6");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut params = Params::default();
+        assert!(matches!(
+            params.add_config("2022.7.limit=1\n2022.7 limit"),
+            Err(ParamError::Syntax(e)) if e.line == 2
+        ));
+
+        params.add("2022.7.limmit=1").unwrap();
+        params.add("2022.8.limit=-1").unwrap();
+        let mut counter = Counter::default();
+        assert_eq!(
+            params.apply(2022, 7, &mut counter),
+            Err(ParamError::unknown("limmit"))
+        );
+        assert!(matches!(
+            params.apply(2022, 8, &mut counter),
+            Err(ParamError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_assign_file() {
+        let mut path = String::new();
+        assign_file(&mut path, "mesh", "out/drop.stl", &["obj", "stl"]).unwrap();
+        assert_eq!(path, "out/drop.stl");
+        for bad in ["drop.txt", "drop", ".stl"] {
+            let error = assign_file(&mut path, "mesh", bad, &["obj", "stl"]).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "invalid value for `mesh`: expected a .obj or .stl file"
+            );
+        }
+        assert_eq!(path, "out/drop.stl");
+    }
+}
@@ -0,0 +1,416 @@
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A slice of the puzzle input that remembers where it starts in the
+/// original text, so anything parsed out of it can report a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Input<'a> {
+    rest: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self {
+            rest: s,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.rest
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    /// Parses the whole input with `FromStr`, reporting failures at the
+    /// start of this input.
+    pub fn parse<T>(&self) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.rest
+            .parse()
+            .map_err(|e| self.error(format!("invalid value `{}`: {e}", self.rest)))
+    }
+
+    /// Splits off the first `bytes` bytes, returning `(head, tail)`.
+    pub fn split_at(self, bytes: usize) -> (Self, Self) {
+        let (head, rest) = self.rest.split_at(bytes);
+        let (mut line, mut column) = (self.line, self.column);
+        for c in head.chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        (Self { rest: head, ..self }, Self { rest, line, column })
+    }
+
+    pub fn advance(self, bytes: usize) -> Self {
+        self.split_at(bytes).1
+    }
+
+    pub fn trim(self) -> Self {
+        let start = self.rest.len() - self.rest.trim_start().len();
+        let trimmed = self.advance(start);
+        Self {
+            rest: trimmed.rest.trim_end(),
+            ..trimmed
+        }
+    }
+
+    pub fn chars(&self) -> std::str::Chars<'a> {
+        self.rest.chars()
+    }
+
+    pub fn lines(self) -> impl Iterator<Item = Input<'a>> {
+        // same line endings as `str::lines`, no trailing empty line
+        self.rest.lines().enumerate().map(move |(i, l)| Input {
+            rest: l,
+            line: self.line + i,
+            column: if i == 0 { self.column } else { 1 },
+        })
+    }
+
+    /// Groups consecutive non-blank lines into blocks.
+    pub fn blocks(self) -> impl Iterator<Item = Input<'a>> {
+        let mut remaining = self;
+        std::iter::from_fn(move || {
+            let start = remaining
+                .rest
+                .find(|c: char| !c.is_ascii_whitespace())
+                .map(|i| remaining.rest[..i].rfind('\n').map_or(0, |n| n + 1))?;
+            remaining = remaining.advance(start);
+
+            let end = remaining
+                .rest
+                .find("\n\n")
+                .or_else(|| remaining.rest.find("\n\r\n"))
+                .unwrap_or(remaining.rest.len());
+            let (block, rest) = remaining.split_at(end);
+            remaining = rest;
+            Some(block.trim_end())
+        })
+    }
+
+    fn trim_end(self) -> Self {
+        Self {
+            rest: self.rest.trim_end(),
+            ..self
+        }
+    }
+}
+
+pub type ParseResult<'a, T> = Result<(T, Input<'a>), ParseError>;
+
+pub trait Parser<'a, T> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T>;
+
+    fn map<U>(self, f: impl Fn(T) -> U) -> impl Parser<'a, U>
+    where
+        Self: Sized,
+    {
+        move |input: Input<'a>| self.parse(input).map(|(v, rest)| (f(v), rest))
+    }
+}
+
+impl<'a, T, F> Parser<'a, T> for F
+where
+    F: Fn(Input<'a>) -> ParseResult<'a, T>,
+{
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+/// Runs `parser` and fails unless it consumed the whole input.
+pub fn complete<'a, T>(parser: impl Parser<'a, T>, input: Input<'a>) -> Result<T, ParseError> {
+    let (value, rest) = parser.parse(input)?;
+    if rest.is_empty() {
+        Ok(value)
+    } else {
+        Err(rest.error(format!("unexpected trailing input `{}`", rest.rest)))
+    }
+}
+
+/// Parses every line of `s` as a `T`.
+pub fn parse_lines<T>(s: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    Input::new(s).lines().map(|l| l.parse()).collect()
+}
+
+pub fn literal<'a, 'p>(expected: &'p str) -> impl Parser<'a, ()> + 'p {
+    move |input: Input<'a>| {
+        if input.rest.starts_with(expected) {
+            Ok(((), input.advance(expected.len())))
+        } else {
+            Err(input.error(format!("expected `{expected}`")))
+        }
+    }
+}
+
+/// Matches the first of `options` the input starts with.
+pub fn one_of<'a, 'p>(options: &'p [&'p str]) -> impl Parser<'a, &'a str> + 'p {
+    move |input: Input<'a>| {
+        options
+            .iter()
+            .find(|o| input.rest.starts_with(**o))
+            .map(|o| {
+                let (head, rest) = input.split_at(o.len());
+                (head.rest, rest)
+            })
+            .ok_or_else(|| input.error(format!("expected one of {options:?}")))
+    }
+}
+
+pub fn take_while<'a>(pred: impl Fn(char) -> bool) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        let end = input.rest.find(|c| !pred(c)).unwrap_or(input.rest.len());
+        let (head, rest) = input.split_at(end);
+        Ok((head.rest, rest))
+    }
+}
+
+/// Returns the slice of input consumed by `parser` instead of its value.
+pub fn spanned<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, Input<'a>> {
+    move |input: Input<'a>| {
+        let (_, rest) = parser.parse(input)?;
+        Ok(input.split_at(input.rest.len() - rest.rest.len()))
+    }
+}
+
+/// A non-empty run of alphanumeric characters.
+pub fn word<'a>() -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        let (word, rest) = take_while(|c| c.is_alphanumeric()).parse(input)?;
+        if word.is_empty() {
+            Err(input.error("expected a word"))
+        } else {
+            Ok((word, rest))
+        }
+    }
+}
+
+pub fn unsigned<'a, T>() -> impl Parser<'a, T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    move |input: Input<'a>| {
+        let (digits, rest) = take_while(|c| c.is_ascii_digit()).parse(input)?;
+        if digits.is_empty() {
+            return Err(input.error("expected a number"));
+        }
+        let value = digits
+            .parse()
+            .map_err(|e| input.error(format!("invalid number `{digits}`: {e}")))?;
+        Ok((value, rest))
+    }
+}
+
+pub fn signed<'a, T>() -> impl Parser<'a, T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    move |input: Input<'a>| {
+        let sign = usize::from(input.rest.starts_with(['-', '+']));
+        let (_, rest) = unsigned::<u128>().parse(input.advance(sign))?;
+        let (number, rest) = input.split_at(input.rest.len() - rest.rest.len());
+        let value = number
+            .rest
+            .trim_start_matches('+')
+            .parse()
+            .map_err(|e| input.error(format!("invalid number `{}`: {e}", number.rest)))?;
+        Ok((value, rest))
+    }
+}
+
+pub fn pair<'a, A, B>(
+    first: impl Parser<'a, A>,
+    second: impl Parser<'a, B>,
+) -> impl Parser<'a, (A, B)> {
+    move |input: Input<'a>| {
+        let (a, rest) = first.parse(input)?;
+        let (b, rest) = second.parse(rest)?;
+        Ok(((a, b), rest))
+    }
+}
+
+pub fn preceded<'a, A, B>(
+    prefix: impl Parser<'a, A>,
+    parser: impl Parser<'a, B>,
+) -> impl Parser<'a, B> {
+    pair(prefix, parser).map(|(_, b)| b)
+}
+
+pub fn terminated<'a, A, B>(
+    parser: impl Parser<'a, A>,
+    suffix: impl Parser<'a, B>,
+) -> impl Parser<'a, A> {
+    pair(parser, suffix).map(|(a, _)| a)
+}
+
+/// Skips any whitespace before `parser`.
+pub fn token<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    preceded(take_while(char::is_whitespace), parser)
+}
+
+/// Zero or more repetitions of `parser`.
+pub fn many<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |mut input: Input<'a>| {
+        let mut items = Vec::new();
+        while let Ok((item, rest)) = parser.parse(input) {
+            if rest == input {
+                break;
+            }
+            items.push(item);
+            input = rest;
+        }
+        Ok((items, input))
+    }
+}
+
+/// One or more `item`s separated by `sep`.
+pub fn separated<'a, T, S>(
+    item: impl Parser<'a, T>,
+    sep: impl Parser<'a, S>,
+) -> impl Parser<'a, Vec<T>> {
+    move |input: Input<'a>| {
+        let (first, mut input) = item.parse(input)?;
+        let mut items = vec![first];
+        while let Ok((_, rest)) = sep.parse(input) {
+            let (next, rest) = match item.parse(rest) {
+                Ok(next) => next,
+                // an empty separator can't commit us to another item
+                Err(_) if rest == input => break,
+                Err(e) => return Err(e),
+            };
+            items.push(next);
+            input = rest;
+        }
+        Ok((items, input))
+    }
+}
+
+/// Matches a template such as `"Sensor at x={}, y={}"`, returning the text
+/// captured by each `{}`. A placeholder captures up to the next occurrence of
+/// the literal text that follows it, or to the end of the input.
+pub fn pattern<'a, 'p, const N: usize>(template: &'p str) -> impl Parser<'a, [Input<'a>; N]> + 'p {
+    let pieces = template.split("{}").collect::<Vec<&str>>();
+    assert_eq!(
+        pieces.len(),
+        N + 1,
+        "pattern `{template}` should have {N} placeholders"
+    );
+
+    move |input: Input<'a>| {
+        let (_, mut input) = literal(pieces[0]).parse(input)?;
+        let mut captures = [input; N];
+        for (capture, next) in captures.iter_mut().zip(pieces.iter().skip(1)) {
+            let end = if next.is_empty() {
+                input.rest.len()
+            } else {
+                input
+                    .rest
+                    .find(next)
+                    .ok_or_else(|| input.error(format!("expected `{next}`")))?
+            };
+            let (head, rest) = input.split_at(end);
+            *capture = head;
+            input = rest.advance(next.len());
+        }
+        Ok((captures, input))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_numbers() {
+        let input = Input::new("-12,+3,45");
+        let values = complete(separated(signed::<i32>(), literal(",")), input).unwrap();
+        assert_eq!(values, vec![-12, 3, 45]);
+
+        let err = complete(unsigned::<u8>(), Input::new("300")).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+    }
+
+    #[test]
+    fn test_pattern() {
+        let line = Input::new("Sensor at x=2, y=-18: closest");
+        let [x, y] = complete(pattern("Sensor at x={}, y={}: closest"), line).unwrap();
+        assert_eq!(x.parse::<i64>().unwrap(), 2);
+        assert_eq!(y.parse::<i64>().unwrap(), -18);
+        assert_eq!(y.column(), 18);
+    }
+
+    #[test]
+    fn test_error_position() {
+        let input = Input::new("1 -> 2\n3 -> x4\n");
+        let err = input
+            .lines()
+            .map(|l| complete(separated(unsigned::<u32>(), literal(" -> ")), l))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 6));
+        assert_eq!(err.to_string(), "line 2, column 6: expected a number");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = Input::new("a\nb\n\n\nc\n");
+        let blocks = input.blocks().collect::<Vec<Input>>();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].as_str(), "a\nb");
+        assert_eq!((blocks[1].as_str(), blocks[1].line()), ("c", 5));
+    }
+}
@@ -0,0 +1,143 @@
+use std::{collections::VecDeque, hash::Hash};
+
+use crate::utils::{FxHashMap, FxHashSet};
+
+/// Breadth-first search over unit-cost moves, returning the first state that
+/// satisfies `goal` along with its distance from the nearest start.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Option<(S, usize)>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = S>,
+{
+    let mut queue = VecDeque::new();
+    let mut seen = FxHashSet::default();
+    for start in starts {
+        if seen.insert(start.clone()) {
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((state, distance)) = queue.pop_front() {
+        if goal(&state) {
+            return Some((state, distance));
+        }
+        for next in successors(&state) {
+            if seen.insert(next.clone()) {
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Like [`bfs`], but returns every state on the way from a start to the
+/// goal, both ends included.
+#[allow(dead_code)]
+pub fn bfs_path<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Option<Vec<S>>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = S>,
+{
+    let mut queue = VecDeque::new();
+    let mut parents: FxHashMap<S, Option<S>> = FxHashMap::default();
+    for start in starts {
+        if !parents.contains_key(&start) {
+            parents.insert(start.clone(), None);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(state) = queue.pop_front() {
+        if goal(&state) {
+            let mut path = vec![state];
+            while let Some(Some(parent)) = parents.get(path.last().unwrap()) {
+                path.push(parent.clone());
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in successors(&state) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(state.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// The distance from the nearest start to every reachable state.
+pub fn bfs_distances<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+) -> FxHashMap<S, usize>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = S>,
+{
+    let mut queue = VecDeque::new();
+    let mut distances = FxHashMap::default();
+    for start in starts {
+        if !distances.contains_key(&start) {
+            distances.insert(start.clone(), 0);
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((state, distance)) = queue.pop_front() {
+        for next in successors(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // a 5x5 grid with a wall down column 2, open only at the bottom
+    fn neighbours(&(x, y): &(i32, i32)) -> Vec<(i32, i32)> {
+        [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|&(x, y)| (0..5).contains(&x) && (0..5).contains(&y) && (x != 2 || y == 4))
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        assert_eq!(
+            bfs([(0, 0)], neighbours, |p| *p == (4, 0)),
+            Some(((4, 0), 12))
+        );
+        assert_eq!(bfs([(0, 0)], neighbours, |p| *p == (9, 9)), None);
+    }
+
+    #[test]
+    fn test_bfs_path() {
+        let path = bfs_path([(0, 0)], neighbours, |p| *p == (4, 0)).unwrap();
+        assert_eq!(path.len(), 13);
+        assert_eq!(path[0], (0, 0));
+        assert!(path.contains(&(2, 4)));
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let distances = bfs_distances([(4, 0), (0, 0)], neighbours);
+        assert_eq!(distances.len(), 21);
+        assert_eq!(distances[&(2, 4)], 6);
+        assert_eq!(distances[&(3, 0)], 1);
+    }
+}
@@ -1,7 +1,8 @@
-use std::{sync::mpsc, thread::available_parallelism, time::Instant};
+use std::time::Instant;
 
 use crate::{
-    utils::{DayPerfMetric, ThreadPool},
+    params::{ParamError, Params},
+    utils::DayPerfMetric,
     year_2020, year_2021, year_2022,
 };
 
@@ -10,6 +11,12 @@ pub trait Solver {
     fn with_input(&mut self, input: &str);
     fn solve_part1(&self) -> String;
     fn solve_part2(&self) -> String;
+
+    /// Overrides one of the day's tunable values, such as a target row or a
+    /// round count. Days without any reject every name.
+    fn set_param(&mut self, name: &str, _value: &str) -> Result<(), ParamError> {
+        Err(ParamError::unknown(name))
+    }
 }
 
 #[allow(clippy::box_default)]
@@ -42,15 +49,21 @@ pub fn get_solver(year: usize, day: usize) -> Option<Box<dyn Solver>> {
         (2022, 16) => Some(Box::new(year_2022::day_16::Solution::default())),
         (2022, 17) => Some(Box::new(year_2022::day_17::Solution::default())),
         (2022, 18) => Some(Box::new(year_2022::day_18::Solution::default())),
-        // (2022, 19) => Some(Box::new(year_2022::day_19::Solution::default())),
+        (2022, 19) => Some(Box::new(year_2022::day_19::Solution::default())),
         (2022, 20) => Some(Box::new(year_2022::day_20::Solution::default())),
         (2022, 21) => Some(Box::new(year_2022::day_21::Solution::default())),
+        (2022, 22) => Some(Box::new(year_2022::day_22::Solution::default())),
+        (2022, 23) => Some(Box::new(year_2022::day_23::Solution::default())),
+        (2022, 24) => Some(Box::new(year_2022::day_24::Solution::default())),
+        (2022, 25) => Some(Box::new(year_2022::day_25::Solution::default())),
         _ => None,
     }
 }
 
-pub fn run_all(year: usize, runs: usize) -> Vec<DayPerfMetric> {
-    let results: Vec<DayPerfMetric> = (1..=25).flat_map(|day| run(year, day, runs)).collect();
+pub fn run_all(year: usize, runs: usize, params: &Params) -> Vec<DayPerfMetric> {
+    let results: Vec<DayPerfMetric> = (1..=25)
+        .flat_map(|day| run(year, day, runs, params))
+        .collect();
 
     println!("finished");
     // results.sort_by_key(|r| r.day);
@@ -58,13 +71,21 @@ pub fn run_all(year: usize, runs: usize) -> Vec<DayPerfMetric> {
     results
 }
 
-pub fn run(year: usize, day: usize, runs: usize) -> Option<DayPerfMetric> {
+fn get_configured_solver(year: usize, day: usize, params: &Params) -> Option<Box<dyn Solver>> {
+    let mut solver = get_solver(year, day)?;
+    params
+        .apply(year, day, solver.as_mut())
+        .expect("parameters should be checked before running");
+    Some(solver)
+}
+
+pub fn run(year: usize, day: usize, runs: usize, params: &Params) -> Option<DayPerfMetric> {
     get_solver(year, day)?;
 
     let part1_start = Instant::now();
     let mut solution1 = "".to_string();
     for _ in 0..runs {
-        if let Some(mut solver) = get_solver(year, day) {
+        if let Some(mut solver) = get_configured_solver(year, day, params) {
             solver.with_input(solver.get_input());
             solution1 = solver.solve_part1();
         }
@@ -74,7 +95,7 @@ pub fn run(year: usize, day: usize, runs: usize) -> Option<DayPerfMetric> {
     let part2_start = Instant::now();
     let mut solution2 = "".to_string();
     for _ in 0..runs {
-        if let Some(mut solver) = get_solver(year, day) {
+        if let Some(mut solver) = get_configured_solver(year, day, params) {
             solver.with_input(solver.get_input());
             solution2 = solver.solve_part2();
         }
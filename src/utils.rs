@@ -31,97 +31,68 @@ pub fn print_time_results(results: Vec<DayPerfMetric>, runs: usize) {
     println!();
 }
 
-// taken directly from the rust book...
+/// Writes a file a param asked for, stopping the run if that fails.
+pub fn write_output(path: &str, contents: &str) {
+    if let Err(e) = std::fs::write(path, contents) {
+        panic!("couldn't write {path}: {e}");
+    }
+}
 
+// the multiply-rotate hash rustc uses internally; much faster than the
+// default SipHash for small keys like grid positions, with no DoS protection
 use std::{
-    sync::{mpsc, Arc, Mutex},
-    thread,
+    collections::{HashMap, HashSet},
+    hash::{BuildHasherDefault, Hasher},
 };
 
-pub struct ThreadPool {
-    workers: Vec<Worker>,
-    sender: Option<mpsc::Sender<Job>>,
+#[derive(Default, Clone, Copy)]
+pub struct FxHasher {
+    hash: u64,
 }
 
-type Job = Box<dyn FnOnce() + Send + 'static>;
-
-impl ThreadPool {
-    /// Create a new ThreadPool.
-    ///
-    /// The size is the number of threads in the pool.
-    ///
-    /// # Panics
-    ///
-    /// The `new` function will panic if the size is zero.
-    pub fn new(size: usize) -> ThreadPool {
-        assert!(size > 0);
-
-        let (sender, receiver) = mpsc::channel();
-
-        let receiver = Arc::new(Mutex::new(receiver));
+impl FxHasher {
+    const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
 
-        let mut workers = Vec::with_capacity(size);
-
-        for id in 0..size {
-            workers.push(Worker::new(id, Arc::clone(&receiver)));
-        }
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+    }
+}
 
-        ThreadPool {
-            workers,
-            sender: Some(sender),
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
         }
     }
 
-    pub fn execute<F>(&self, f: F)
-    where
-        F: FnOnce() + Send + 'static,
-    {
-        let job = Box::new(f);
-
-        self.sender.as_ref().unwrap().send(job).unwrap();
+    fn write_u8(&mut self, i: u8) {
+        self.add(i as u64);
     }
-}
 
-impl Drop for ThreadPool {
-    fn drop(&mut self) {
-        drop(self.sender.take());
+    fn write_u32(&mut self, i: u32) {
+        self.add(i as u64);
+    }
 
-        for worker in &mut self.workers {
-            println!("Shutting down worker {}", worker.id);
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
 
-            if let Some(thread) = worker.thread.take() {
-                thread.join().unwrap();
-            }
-        }
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
     }
-}
 
-struct Worker {
-    id: usize,
-    thread: Option<thread::JoinHandle<()>>,
-}
+    fn write_i32(&mut self, i: i32) {
+        self.add(i as u32 as u64);
+    }
 
-impl Worker {
-    fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Worker {
-        let thread = thread::spawn(move || loop {
-            let message = receiver.lock().unwrap().recv();
-
-            match message {
-                Ok(job) => {
-                    println!("Worker {id} got a job; executing.");
-
-                    job();
-                }
-                Err(_) => {
-                    println!("Worker {id} disconnected; shutting down.");
-                    break;
-                }
-            }
-        });
-
-        Worker {
-            id,
-            thread: Some(thread),
-        }
+    fn finish(&self) -> u64 {
+        // the multiply leaves the best mixed bits at the top, but hash tables
+        // pick buckets with the bottom ones
+        self.hash.rotate_left(26)
     }
 }
+
+pub type FxHashMap<K, V> = HashMap<K, V, BuildHasherDefault<FxHasher>>;
+pub type FxHashSet<T> = HashSet<T, BuildHasherDefault<FxHasher>>;
@@ -1,4 +1,4 @@
-use crate::solver::Solver;
+use crate::{parse::Input, solver::Solver};
 
 use std::collections::HashSet;
 
@@ -13,7 +13,11 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input.lines().map(|l| l.parse().unwrap()).collect()
+        self.input = Input::new(input)
+            .lines()
+            .map(|l| l.parse())
+            .collect::<Result<_, _>>()
+            .unwrap()
     }
 
     fn solve_part1(&self) -> String {
@@ -1,4 +1,7 @@
-use crate::solver::Solver;
+use crate::{
+    parse::{complete, pattern, Input, ParseError},
+    solver::Solver,
+};
 
 #[derive(Debug)]
 struct Password {
@@ -33,21 +36,15 @@ impl Password {
     }
 }
 
-impl From<&str> for Password {
-    fn from(input: &str) -> Self {
-        let (min, rest) = input.split_once('-').unwrap();
-        let min = min.parse::<u16>().unwrap();
-        let (max, rest) = rest.split_once(' ').unwrap();
-        let max = max.parse::<u16>().unwrap();
-        let rest = rest.split(": ").collect::<Vec<&str>>();
-        let letter = rest[0].chars().next().unwrap();
-        let pass = rest[1].to_string();
-        Password {
-            start: min,
-            end: max,
-            letter,
-            pass,
-        }
+impl Password {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let [start, end, letter, pass] = complete(pattern("{}-{} {}: {}"), input)?;
+        Ok(Password {
+            start: start.parse()?,
+            end: end.parse()?,
+            letter: letter.parse()?,
+            pass: pass.as_str().to_string(),
+        })
     }
 }
 
@@ -62,7 +59,11 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input.lines().map(|l| l.into()).collect();
+        self.input = Input::new(input)
+            .lines()
+            .map(Password::parse)
+            .collect::<Result<_, _>>()
+            .unwrap();
     }
 
     fn solve_part1(&self) -> String {
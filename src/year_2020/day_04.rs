@@ -1,6 +1,12 @@
 use std::collections::HashMap;
 
-use crate::solver::Solver;
+use crate::{
+    parse::{
+        complete, literal, pair, separated, spanned, take_while, terminated, word, Input,
+        ParseError,
+    },
+    solver::Solver,
+};
 
 #[derive(Debug)]
 struct Passport {
@@ -14,44 +20,31 @@ struct Passport {
                  // cid: Option<String>, // country_id
 }
 
-impl TryFrom<String> for Passport {
-    type Error = String;
-
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        let bits: Vec<&str> = value.trim().split(' ').collect();
-        let mut map = HashMap::new();
-        for bit in bits {
-            if let Some((key, val)) = bit.split_once(':') {
-                map.insert(key, val);
-            } else {
-                return Err(format!("{value} had invalid entry: {bit}"));
-            }
-        }
+impl Passport {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let field = pair(
+            terminated(word(), literal(":")),
+            spanned(take_while(|c| !c.is_ascii_whitespace())),
+        );
+        let whitespace = take_while(|c| c.is_ascii_whitespace());
+        let map = complete(separated(field, whitespace), input)?
+            .into_iter()
+            .collect::<HashMap<&str, Input>>();
+
+        let get = |key: &str| {
+            map.get(key)
+                .ok_or_else(|| input.error(format!("missing {key}")))
+        };
 
         Ok(Passport {
-            byr: map
-                .get("byr")
-                .ok_or("missing byr")?
-                .to_owned()
-                .parse::<usize>()
-                .map_err(|x| format!("failed to parse byr: {x}"))?,
-            iyr: map
-                .get("iyr")
-                .ok_or("missing iyr")?
-                .to_owned()
-                .parse::<usize>()
-                .map_err(|x| format!("failed to parse iyr: {x}"))?,
-            eyr: map
-                .get("eyr")
-                .ok_or("missing eyr")?
-                .to_owned()
-                .parse::<usize>()
-                .map_err(|x| format!("failed to parse eyr: {x}"))?,
-            hgt: map.get("hgt").ok_or("missing hgt")?.to_string(),
-            hcl: map.get("hcl").ok_or("missing hcl")?.to_string(),
-            ecl: map.get("ecl").ok_or("missing ecl")?.to_string(),
-            pid: map.get("pid").ok_or("missing pid")?.to_string(),
-            // cid: map.get("cid").map(|s| s.to_string()),
+            byr: get("byr")?.parse()?,
+            iyr: get("iyr")?.parse()?,
+            eyr: get("eyr")?.parse()?,
+            hgt: get("hgt")?.as_str().to_string(),
+            hcl: get("hcl")?.as_str().to_string(),
+            ecl: get("ecl")?.as_str().to_string(),
+            pid: get("pid")?.as_str().to_string(),
+            // cid: map.get("cid").map(|s| s.as_str().to_string()),
         })
     }
 }
@@ -74,11 +67,11 @@ impl Passport {
 
     fn is_hgt_valid(&self) -> bool {
         if self.hgt.ends_with("in") {
-            if let Ok(h) = self.hgt.trim_end_matches(&['i', 'n']).parse::<u32>() {
+            if let Ok(h) = self.hgt.trim_end_matches(['i', 'n']).parse::<u32>() {
                 return h.ge(&59) && h.le(&76);
             }
         } else if self.hgt.ends_with("cm") {
-            if let Ok(h) = self.hgt.trim_end_matches(&['c', 'm']).parse::<u32>() {
+            if let Ok(h) = self.hgt.trim_end_matches(['c', 'm']).parse::<u32>() {
                 return h.ge(&150) && h.le(&193);
             }
         }
@@ -102,7 +95,7 @@ impl Passport {
 
 #[derive(Default, Debug)]
 pub struct Solution {
-    input: Vec<Result<Passport, String>>,
+    input: Vec<Result<Passport, ParseError>>,
 }
 
 impl Solver for Solution {
@@ -111,19 +104,7 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input
-            .lines()
-            .fold(vec!["".to_string()], |mut acc, line| {
-                if line.is_empty() {
-                    acc.push("".to_string())
-                } else {
-                    acc.last_mut().unwrap().push_str(&format!(" {line}"));
-                }
-                acc
-            })
-            .iter()
-            .map(|s| Passport::try_from(s.to_owned()))
-            .collect();
+        self.input = Input::new(input).blocks().map(Passport::parse).collect();
     }
 
     fn solve_part1(&self) -> String {
@@ -1,4 +1,4 @@
-use crate::solver::Solver;
+use crate::{parse::parse_lines, solver::Solver};
 
 #[derive(Default, Debug)]
 pub struct Solution {
@@ -11,7 +11,7 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input.lines().map(|l| l.parse().unwrap()).collect();
+        self.input = parse_lines(input).unwrap();
     }
 
     fn solve_part1(&self) -> String {
@@ -1,6 +1,7 @@
-use std::str::FromStr;
-
-use crate::solver::Solver;
+use crate::{
+    parse::{complete, literal, pair, preceded, spanned, unsigned, word, Input, ParseError},
+    solver::Solver,
+};
 
 #[derive(Debug)]
 enum Dir {
@@ -9,16 +10,17 @@ enum Dir {
     Up(usize),
 }
 
-impl FromStr for Dir {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (dir, amt) = s.split_once(' ').unwrap();
-        let amt = amt.parse::<usize>().map_err(|_| "nope".to_string())?;
-        match dir {
+impl Dir {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let (dir, amt) = complete(
+            pair(spanned(word()), preceded(literal(" "), unsigned())),
+            input,
+        )?;
+        match dir.as_str() {
             "forward" => Ok(Dir::Forward(amt)),
             "down" => Ok(Dir::Down(amt)),
             "up" => Ok(Dir::Up(amt)),
-            _ => Err("nope".to_string()),
+            other => Err(dir.error(format!("unknown direction `{other}`"))),
         }
     }
 }
@@ -34,7 +36,11 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input.lines().map(|l| l.parse().unwrap()).collect();
+        self.input = Input::new(input)
+            .lines()
+            .map(Dir::parse)
+            .collect::<Result<_, _>>()
+            .unwrap();
     }
 
     fn solve_part1(&self) -> String {
@@ -1,4 +1,4 @@
-use crate::solver::Solver;
+use crate::{parse::Input, solver::Solver};
 
 #[derive(Default)]
 pub struct Solution {
@@ -11,19 +11,11 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        let mut calorie_counts =
-            input
-                .lines()
-                .map(|l| l.parse::<u32>().ok())
-                .fold(vec![0], |mut acc, n| {
-                    if let Some(n) = n {
-                        let total_calories = acc.last_mut().unwrap();
-                        *total_calories += n;
-                    } else {
-                        acc.push(0);
-                    }
-                    acc
-                });
+        let mut calorie_counts = Input::new(input)
+            .blocks()
+            .map(|elf| elf.lines().map(|l| l.parse::<u32>()).sum())
+            .collect::<Result<Vec<u32>, _>>()
+            .unwrap();
 
         calorie_counts.sort();
         calorie_counts.reverse();
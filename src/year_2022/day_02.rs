@@ -1,6 +1,9 @@
 use std::str::FromStr;
 
-use crate::solver::Solver;
+use crate::{
+    parse::{complete, pattern, Input, ParseError},
+    solver::Solver,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 enum Rps {
@@ -92,13 +95,14 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input
+        self.input = Input::new(input)
             .lines()
             .map(|l| {
-                let (left, right) = l.split_once(' ').unwrap();
-                (left.to_owned(), right.to_owned())
+                let [left, right] = complete(pattern("{} {}"), l)?;
+                Ok((left.as_str().to_owned(), right.as_str().to_owned()))
             })
-            .collect();
+            .collect::<Result<_, ParseError>>()
+            .unwrap();
     }
 
     fn solve_part1(&self) -> String {
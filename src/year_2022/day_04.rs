@@ -1,37 +1,12 @@
-use std::str::FromStr;
-
-use crate::solver::Solver;
-
-struct Section {
-    start: usize,
-    end: usize,
-}
-
-impl Section {
-    fn is_contained_by(&self, section: &Section) -> bool {
-        self.start >= section.start && self.end <= section.end
-    }
-
-    fn is_overlapped_by(&self, section: &Section) -> bool {
-        (self.start >= section.start && self.start <= section.end)
-            || (self.end >= section.start && self.end <= section.end)
-    }
-}
-
-impl FromStr for Section {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (start, end) = s.split_once('-').unwrap();
-        Ok(Section {
-            start: start.parse::<usize>().unwrap(),
-            end: end.parse::<usize>().unwrap(),
-        })
-    }
-}
+use crate::{
+    interval::Interval,
+    parse::{complete, pattern, Input, ParseError},
+    solver::Solver,
+};
 
 #[derive(Default)]
 pub struct Solution {
-    input: Vec<(Section, Section)>,
+    input: Vec<(Interval, Interval)>,
 }
 
 impl Solver for Solution {
@@ -40,23 +15,25 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input
+        self.input = Input::new(input)
             .lines()
             .map(|l| {
-                let (first, second) = l.split_once(',').unwrap();
-                (
-                    first.parse::<Section>().unwrap(),
-                    second.parse::<Section>().unwrap(),
-                )
+                let [s1, e1, s2, e2] = complete(pattern("{}-{},{}-{}"), l)?;
+                let range = |start: Input, end: Input| {
+                    Interval::inclusive(start.parse()?, end.parse()?)
+                        .ok_or_else(|| end.error("sections must end before i64::MAX"))
+                };
+                Ok((range(s1, e1)?, range(s2, e2)?))
             })
-            .collect();
+            .collect::<Result<_, ParseError>>()
+            .unwrap();
     }
 
     fn solve_part1(&self) -> String {
         self.input
             .iter()
             .filter(|(first, second)| {
-                first.is_contained_by(second) || second.is_contained_by(first)
+                first.contains_interval(second) || second.contains_interval(first)
             })
             .count()
             .to_string()
@@ -64,16 +41,58 @@ impl Solver for Solution {
     fn solve_part2(&self) -> String {
         self.input
             .iter()
-            .filter(|(first, second)| {
-                first.is_overlapped_by(second) || second.is_contained_by(first)
-            })
+            .filter(|(first, second)| first.overlaps(second))
             .count()
             .to_string()
     }
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    fn get_example_input<'a>() -> &'a str {
+        "\
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8"
+    }
+
+    #[test]
+    fn test_solution_example1() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "2");
+    }
+
+    #[test]
+    fn test_solution_example2() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "4");
+    }
+
+    #[test]
+    fn test_solution_part1() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "573");
+    }
+
+    #[test]
+    fn test_solution_part2() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "867");
+    }
+}
 
 const INPUT: &str = "71-89,66-70
 24-70,23-55
@@ -1,4 +1,7 @@
-use crate::solver::Solver;
+use crate::{
+    parse::{complete, pattern, Input},
+    solver::Solver,
+};
 
 #[derive(Default)]
 pub struct Solution {
@@ -12,10 +15,11 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        for line in input.lines() {
-            if line.is_empty() {
-                break;
-            }
+        let mut blocks = Input::new(input).blocks();
+        let drawing = blocks.next().unwrap();
+        let moves = blocks.next().unwrap();
+
+        for line in drawing.lines() {
             line.chars()
                 .enumerate()
                 .skip(1)
@@ -33,13 +37,8 @@ impl Solver for Solution {
                 });
         }
 
-        for line in input.lines() {
-            if !line.starts_with("move") {
-                continue;
-            }
-            let line = line.strip_prefix("move ").unwrap();
-            let (amount, rest) = line.split_once(" from ").unwrap();
-            let (from, to) = rest.split_once(" to ").unwrap();
+        for line in moves.lines() {
+            let [amount, from, to] = complete(pattern("move {} from {} to {}"), line).unwrap();
             let amount = amount.parse::<usize>().unwrap();
             let from = from.parse::<usize>().unwrap() - 1;
             let to = to.parse::<usize>().unwrap() - 1;
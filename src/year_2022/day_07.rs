@@ -1,78 +1,205 @@
-use std::{collections::HashMap, str::FromStr, vec::Drain};
+use std::fmt::Display;
 
-use crate::solver::Solver;
+use crate::{
+    params::{assign, ParamError},
+    parse::{complete, literal, pair, preceded, take_while, unsigned, Input, ParseError},
+    solver::Solver,
+};
 
-#[derive(Debug, Default)]
-struct Directory {
-    children: HashMap<String, Directory>,
-    size: usize,
+/// Index of a node in [`FileSystem::nodes`].
+type NodeId = usize;
+
+#[derive(Debug)]
+enum NodeKind {
+    Dir { children: Vec<NodeId> },
+    File { size: usize },
 }
 
-impl Directory {
-    fn iter(&self) -> DirectoryIter {
-        DirectoryIter::new(self)
-    }
+#[derive(Debug)]
+struct Node {
+    name: String,
+    parent: Option<NodeId>,
+    kind: NodeKind,
+}
+
+/// Every directory and file seen in the terminal session, stored in one
+/// arena with the root at index 0. Nodes are only ever added after their
+/// parent, so children always come later in the arena than their parents.
+#[derive(Debug)]
+struct FileSystem {
+    nodes: Vec<Node>,
 }
 
-fn create_dir(input: &mut Drain<&str>, dir: &mut Directory) -> usize {
-    while let Some(next) = input.next() {
-        let com = next.parse::<Command>().unwrap();
-        match com {
-            Command::CD(name) => {
-                if name == ".." {
-                    return dir.size;
-                } else if name != "/" {
-                    dir.size += create_dir(input, dir.children.get_mut(&name).unwrap());
+impl FileSystem {
+    const ROOT: NodeId = 0;
+
+    fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                kind: NodeKind::Dir {
+                    children: Vec::new(),
+                },
+            }],
+        }
+    }
+
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let mut fs = Self::new();
+        let mut cwd = Self::ROOT;
+        for line in input.lines() {
+            match Command::parse(line)? {
+                Command::CD(name) => {
+                    cwd = match name.as_str() {
+                        "/" => Self::ROOT,
+                        // like a shell, `cd ..` at the root stays put
+                        ".." => fs.nodes[cwd].parent.unwrap_or(Self::ROOT),
+                        _ => fs
+                            .child(cwd, &name)
+                            .filter(|id| fs.is_dir(*id))
+                            .ok_or_else(|| line.error(format!("no directory `{name}`")))?,
+                    }
+                }
+                Command::LS => {}
+                Command::Dir(name) => {
+                    fs.add(
+                        cwd,
+                        name,
+                        NodeKind::Dir {
+                            children: Vec::new(),
+                        },
+                    );
+                }
+                Command::File(size, name) => {
+                    fs.add(cwd, name, NodeKind::File { size });
                 }
             }
-            Command::LS => {}
-            Command::Dir(name) => {
-                dir.children.insert(
-                    name,
-                    Directory {
-                        children: HashMap::new(),
-                        size: 0,
-                    },
-                );
-            }
-            Command::File(size) => dir.size += size,
-        };
+        }
+        Ok(fs)
     }
 
-    dir.size
-}
+    fn children(&self, dir: NodeId) -> &[NodeId] {
+        match &self.nodes[dir].kind {
+            NodeKind::Dir { children } => children,
+            NodeKind::File { .. } => &[],
+        }
+    }
 
-impl From<&str> for Directory {
-    fn from(input: &str) -> Self {
-        let mut dir = Directory::default();
-        let mut lines = input.lines().collect::<Vec<&str>>();
-        let mut input = lines.drain(0..);
+    fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        self.children(dir)
+            .iter()
+            .copied()
+            .find(|id| self.nodes[*id].name == name)
+    }
+
+    fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].kind, NodeKind::Dir { .. })
+    }
+
+    /// Adds an entry to `dir`, unless listing it again found one that's
+    /// already there.
+    fn add(&mut self, dir: NodeId, name: String, kind: NodeKind) -> NodeId {
+        if let Some(id) = self.child(dir, &name) {
+            return id;
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name,
+            parent: Some(dir),
+            kind,
+        });
+        if let NodeKind::Dir { children } = &mut self.nodes[dir].kind {
+            children.push(id);
+        }
+        id
+    }
 
-        create_dir(&mut input, &mut dir);
-        dir
+    fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len()).filter(|id| self.is_dir(*id))
+    }
+
+    /// The full path of a node, such as `/a/e`.
+    fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut node = Some(id);
+        while let Some(id) = node.filter(|id| *id != Self::ROOT) {
+            names.push(self.nodes[id].name.as_str());
+            node = self.nodes[id].parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// Finds the node at an absolute path such as `/a/e`.
+    fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.strip_prefix('/')?
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(Self::ROOT, |dir, name| self.child(dir, name))
+    }
+
+    /// The total size of everything under each node, like `du`, indexed by
+    /// [`NodeId`].
+    fn du(&self) -> Vec<usize> {
+        let mut sizes = self
+            .nodes
+            .iter()
+            .map(|n| match n.kind {
+                NodeKind::File { size } => size,
+                NodeKind::Dir { .. } => 0,
+            })
+            .collect::<Vec<usize>>();
+        // children come after their parents, so walking backwards finishes
+        // every node before adding it to its parent
+        for id in (1..self.nodes.len()).rev() {
+            if let Some(parent) = self.nodes[id].parent {
+                sizes[parent] += sizes[id];
+            }
+        }
+        sizes
+    }
+
+    fn fmt_node(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        id: NodeId,
+        depth: usize,
+    ) -> std::fmt::Result {
+        let node = &self.nodes[id];
+        let indent = "  ".repeat(depth);
+        match node.kind {
+            NodeKind::Dir { .. } => writeln!(f, "{indent}- {} (dir)", node.name)?,
+            NodeKind::File { size } => writeln!(f, "{indent}- {} (file, size={size})", node.name)?,
+        }
+        self.children(id)
+            .iter()
+            .try_for_each(|child| self.fmt_node(f, *child, depth + 1))
+    }
+
+    /// The tree below `id`, for printing.
+    fn tree(&self, id: NodeId) -> Tree<'_> {
+        Tree { fs: self, id }
     }
 }
 
-struct DirectoryIter<'a> {
-    stack: Vec<&'a Directory>,
+/// Prints a tree the way the puzzle does, entries in the order they were
+/// listed.
+struct Tree<'a> {
+    fs: &'a FileSystem,
+    id: NodeId,
 }
 
-impl<'a> DirectoryIter<'a> {
-    fn new(root: &'a Directory) -> Self {
-        Self { stack: vec![root] }
+impl Display for Tree<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fs.fmt_node(f, self.id, 0)
     }
 }
 
-impl<'a> Iterator for DirectoryIter<'a> {
-    type Item = &'a Directory;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(d) = self.stack.pop() {
-            self.stack.extend(d.children.values());
-            Some(d)
-        } else {
-            None
-        }
+impl Display for FileSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.tree(Self::ROOT).fmt(f)
     }
 }
 
@@ -81,31 +208,64 @@ enum Command {
     CD(String),
     LS,
     Dir(String),
-    File(usize),
+    File(usize, String),
 }
 
-impl FromStr for Command {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.starts_with("$ cd") {
-            Ok(Command::CD(s.trim_start_matches("$ cd ").to_string()))
-        } else if s.starts_with("$ ls") {
+impl Command {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let name = |prefix| complete(preceded(literal(prefix), take_while(|_| true)), input);
+        if let Ok(name) = name("$ cd ") {
+            Ok(Command::CD(name.to_string()))
+        } else if input.as_str() == "$ ls" {
             Ok(Command::LS)
-        } else if s.starts_with("dir") {
-            Ok(Command::Dir(s.trim_start_matches("dir ").to_string()))
+        } else if let Ok(name) = name("dir ") {
+            Ok(Command::Dir(name.to_string()))
         } else {
-            let (size, _name) = s
-                .split_once(' ')
-                .ok_or_else(|| "bad file descriptor!".to_string())?;
-            let size = size.parse::<usize>().map_err(|e| e.to_string())?;
-            Ok(Command::File(size))
+            let (size, name) = complete(
+                pair(unsigned(), preceded(literal(" "), take_while(|_| true))),
+                input,
+            )?;
+            Ok(Command::File(size, name.to_string()))
         }
     }
 }
 
-#[derive(Default)]
 pub struct Solution {
-    directory: Directory,
+    fs: FileSystem,
+    sizes: Vec<usize>,
+    small_dir_limit: usize,
+    disk_size: usize,
+    update_size: usize,
+    // print the tree below this directory
+    tree: String,
+    // print the path of the directory part 2 deletes
+    show_path: bool,
+}
+
+impl Default for Solution {
+    fn default() -> Self {
+        Self {
+            fs: FileSystem::new(),
+            sizes: vec![0],
+            small_dir_limit: 100000,
+            disk_size: 70000000,
+            update_size: 30000000,
+            tree: String::new(),
+            show_path: false,
+        }
+    }
+}
+
+impl Solution {
+    /// The smallest directory that frees up enough space for the update.
+    fn directory_to_delete(&self) -> Option<NodeId> {
+        let free_space = self.disk_size.saturating_sub(self.sizes[FileSystem::ROOT]);
+        let amount_to_clean_up = self.update_size.saturating_sub(free_space);
+        self.fs
+            .directories()
+            .filter(|d| self.sizes[*d] >= amount_to_clean_up)
+            .min_by_key(|d| self.sizes[*d])
+    }
 }
 
 impl Solver for Solution {
@@ -114,34 +274,48 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.directory = Directory::from(input);
+        self.fs = FileSystem::parse(Input::new(input)).unwrap();
+        self.sizes = self.fs.du();
     }
 
     fn solve_part1(&self) -> String {
-        let target_size = 100000;
-        self.directory
-            .iter()
-            .filter(|d| d.size <= target_size)
-            .map(|d| d.size)
+        if !self.tree.is_empty() {
+            let dir = self.fs.lookup(&self.tree);
+            let dir = dir.unwrap_or_else(|| panic!("no directory at `{}`", self.tree));
+            print!("{}", self.fs.tree(dir));
+        }
+
+        self.fs
+            .directories()
+            .map(|d| self.sizes[d])
+            .filter(|size| *size <= self.small_dir_limit)
             .sum::<usize>()
             .to_string()
     }
 
     fn solve_part2(&self) -> String {
-        let total_space = 70000000;
-        let target_free_space = 30000000;
-        let current_used_space = self.directory.size;
-        let amount_to_clean_up = target_free_space - (total_space - current_used_space);
-
-        let mut big_dirs = self
-            .directory
-            .iter()
-            .filter(|d| d.size >= amount_to_clean_up)
-            .collect::<Vec<&Directory>>();
-
-        big_dirs.sort_by_key(|d| d.size);
+        let Some(dir) = self.directory_to_delete() else {
+            return "no directory is big enough".to_string();
+        };
+        if self.show_path {
+            println!("delete {} to free {}", self.fs.path(dir), self.sizes[dir]);
+        }
+        self.sizes[dir].to_string()
+    }
 
-        big_dirs[0].size.to_string()
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "small_dir_limit" => assign(&mut self.small_dir_limit, name, value),
+            "disk_size" => assign(&mut self.disk_size, name, value),
+            "update_size" => assign(&mut self.update_size, name, value),
+            "tree" if !value.starts_with('/') => Err(ParamError::Invalid {
+                name: name.to_string(),
+                message: "expected an absolute path such as `/a/e`".to_string(),
+            }),
+            "tree" => assign(&mut self.tree, name, value),
+            "show_path" => assign(&mut self.show_path, name, value),
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
@@ -180,7 +354,53 @@ $ ls
     fn test_parse() {
         let mut solver = Solution::default();
         solver.with_input(get_input());
-        assert_eq!(solver.directory.size, 48381165);
+        assert_eq!(solver.sizes[FileSystem::ROOT], 48381165);
+    }
+
+    #[test]
+    fn test_tree() {
+        // jumping back to the root and listing again mustn't count twice
+        let input = format!("{}\n$ cd /\n$ ls\ndir a\n14848514 b.txt", get_input());
+        let fs = FileSystem::parse(Input::new(&input)).unwrap();
+        assert_eq!(
+            fs.to_string(),
+            "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)
+"
+        );
+
+        let sizes = fs.du();
+        let e = fs.lookup("/a/e").unwrap();
+        assert_eq!((fs.path(e).as_str(), sizes[e]), ("/a/e", 584));
+        assert_eq!(
+            fs.tree(e).to_string(),
+            "- e (dir)\n  - i (file, size=584)\n"
+        );
+        assert_eq!(sizes[fs.lookup("/a").unwrap()], 94853);
+        assert_eq!(fs.lookup("/"), Some(FileSystem::ROOT));
+        assert_eq!(fs.lookup("/a/x"), None);
+    }
+
+    #[test]
+    fn test_directory_to_delete() {
+        let mut solver = Solution::default();
+        solver.with_input(get_input());
+        let dir = solver.directory_to_delete().unwrap();
+        assert_eq!(solver.fs.path(dir), "/d");
+        assert!(solver.set_param("tree", "a/e").is_err());
     }
 
     #[test]
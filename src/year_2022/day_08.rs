@@ -1,71 +1,123 @@
-use crate::solver::Solver;
-
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-type Tree = u8;
+use crate::{
+    params::{assign_file, ParamError},
+    parse::{Input, ParseError},
+    solver::Solver,
+    utils::write_output,
+};
 
 #[derive(Default, Debug)]
 struct Forest {
-    trees: Vec<Vec<u8>>,
-    rows_len: usize,
-    cols_len: usize,
+    // row by row
+    heights: Vec<u8>,
+    width: usize,
+    height: usize,
 }
 
 impl Forest {
-    fn iter_dir<'a>(&'a self, row: usize, col: usize, dir: &'a Direction) -> ForestDirectionalIter {
-        ForestDirectionalIter::new(self, row, col, dir)
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let mut forest = Self::default();
+        for line in input.lines() {
+            if forest.height > 0 && line.as_str().len() != forest.width {
+                return Err(line.error(format!("expected a row of {} trees", forest.width)));
+            }
+            for (col, c) in line.chars().enumerate() {
+                let height = c
+                    .to_digit(10)
+                    .ok_or_else(|| line.advance(col).error(format!("invalid height `{c}`")))?;
+                forest.heights.push(height as u8);
+            }
+            forest.width = line.as_str().len();
+            forest.height += 1;
+        }
+        Ok(forest)
     }
 
-    fn get(&self, row: usize, col: usize) -> Option<&Tree> {
-        self.trees.get(row)?.get(col)
-    }
-}
+    /// Works out what every tree can see, by sweeping each row and column
+    /// once in both directions.
+    fn scores(&self) -> Scores {
+        let mut scores = Scores {
+            width: self.width,
+            height: self.height,
+            visible: vec![false; self.heights.len()],
+            scenic: vec![1; self.heights.len()],
+        };
 
-struct ForestDirectionalIter<'a> {
-    forest: &'a Forest,
-    row: isize,
-    col: isize,
-    dir: &'a Direction,
-}
+        let mut stack = Vec::new();
+        for row in 0..self.height {
+            let cells = row * self.width..(row + 1) * self.width;
+            self.sweep(cells.clone(), &mut scores, &mut stack);
+            self.sweep(cells.rev(), &mut scores, &mut stack);
+        }
+        for col in 0..self.width {
+            let cells = (col..self.heights.len()).step_by(self.width);
+            self.sweep(cells.clone(), &mut scores, &mut stack);
+            self.sweep(cells.rev(), &mut scores, &mut stack);
+        }
+        scores
+    }
 
-impl<'a> ForestDirectionalIter<'a> {
-    fn new(forest: &'a Forest, row: usize, col: usize, dir: &'a Direction) -> Self {
-        Self {
-            forest,
-            row: row as isize,
-            col: col as isize,
-            dir,
+    /// Walks along one line of trees, looking back the way it came from
+    /// each of them. The stack holds the trees not yet hidden behind a
+    /// taller one later on, so their heights never increase and the first
+    /// one at least as tall as the current tree is the one blocking its
+    /// view.
+    fn sweep(
+        &self,
+        cells: impl Iterator<Item = usize>,
+        scores: &mut Scores,
+        stack: &mut Vec<(usize, u8)>,
+    ) {
+        stack.clear();
+        for (pos, cell) in cells.enumerate() {
+            let height = self.heights[cell];
+            while stack.last().is_some_and(|(_, h)| *h < height) {
+                stack.pop();
+            }
+            match stack.last() {
+                Some((blocker, _)) => scores.scenic[cell] *= (pos - blocker) as u64,
+                None => {
+                    scores.visible[cell] = true;
+                    scores.scenic[cell] *= pos as u64;
+                }
+            }
+            stack.push((pos, height));
         }
     }
 }
 
-impl<'a> Iterator for ForestDirectionalIter<'a> {
-    type Item = &'a Tree;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.dir {
-            Direction::Up => self.row -= 1,
-            Direction::Down => self.row += 1,
-            Direction::Left => self.col -= 1,
-            Direction::Right => self.col += 1,
-        };
+/// What each tree sees, laid out row by row like [`Forest::heights`].
+#[derive(Default, Debug)]
+struct Scores {
+    width: usize,
+    height: usize,
+    // seen from outside the forest in at least one direction
+    visible: Vec<bool>,
+    scenic: Vec<u64>,
+}
 
-        if self.row < 0 || self.col < 0 {
-            return None;
+impl Scores {
+    /// Exports the scenic scores as a plain PGM image, brighter for higher
+    /// scores.
+    fn heatmap(&self) -> String {
+        let max = self.scenic.iter().max().copied().unwrap_or(0).max(1);
+        let mut pgm = format!("P2\n{} {}\n255\n", self.width, self.height);
+        for row in self.scenic.chunks(self.width.max(1)) {
+            let pixels = row
+                .iter()
+                .map(|s| (s * 255 / max).to_string())
+                .collect::<Vec<String>>();
+            pgm.push_str(&pixels.join(" "));
+            pgm.push('\n');
         }
-
-        self.forest.get(self.row as usize, self.col as usize)
+        pgm
     }
 }
 
 #[derive(Default)]
 pub struct Solution {
-    forest: Forest,
+    scores: Scores,
+    // write the scenic scores to this file as a PGM heatmap
+    heatmap: String,
 }
 
 impl Solver for Solution {
@@ -74,89 +126,30 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.forest.trees = input
-            .lines()
-            .map(|l| {
-                l.chars()
-                    .map(|c| c.to_digit(10).unwrap() as Tree)
-                    .collect::<Vec<Tree>>()
-            })
-            .collect::<Vec<Vec<Tree>>>();
-        self.forest.rows_len = self.forest.trees.len();
-        self.forest.cols_len = self.forest.trees.first().unwrap().len();
+        self.scores = Forest::parse(Input::new(input)).unwrap().scores();
     }
 
     fn solve_part1(&self) -> String {
-        let visible_trees = self
-            .forest
-            .trees
+        self.scores
+            .visible
             .iter()
-            .enumerate()
-            .flat_map(|(r, row)| {
-                row.iter().enumerate().filter(move |(c, tree)| {
-                    vec![
-                        Direction::Up,
-                        Direction::Down,
-                        Direction::Left,
-                        Direction::Right,
-                    ]
-                    .iter()
-                    .any(|dir| {
-                        let mut iter = self.forest.iter_dir(r, *c, dir).peekable();
-
-                        if iter.peek().is_none() {
-                            true
-                        } else {
-                            iter.all(|t| tree > &t)
-                        }
-                    })
-                })
-            })
-            .count();
-
-        visible_trees.to_string()
+            .filter(|v| **v)
+            .count()
+            .to_string()
     }
 
     fn solve_part2(&self) -> String {
-        let mut scenic_scores = self
-            .forest
-            .trees
-            .iter()
-            .enumerate()
-            .flat_map(|(r, row)| {
-                row.iter()
-                    .enumerate()
-                    .map(|(c, tree)| {
-                        let scores = vec![
-                            Direction::Up,
-                            Direction::Down,
-                            Direction::Left,
-                            Direction::Right,
-                        ]
-                        .iter()
-                        .map(|dir| {
-                            let iter = self.forest.iter_dir(r, c, dir);
-
-                            let mut score = 0;
-                            for t in iter {
-                                score += 1;
-                                if t >= tree {
-                                    break;
-                                }
-                            }
-                            score
-                        })
-                        .collect::<Vec<usize>>();
-
-                        scores.iter().product()
-                    })
-                    .collect::<Vec<usize>>()
-            })
-            .collect::<Vec<usize>>();
-
-        scenic_scores.sort();
+        if !self.heatmap.is_empty() {
+            write_output(&self.heatmap, &self.scores.heatmap());
+        }
+        self.scores.scenic.iter().max().unwrap_or(&0).to_string()
+    }
 
-        scenic_scores.last().unwrap().to_string()
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "heatmap" => assign_file(&mut self.heatmap, name, value, &["pgm"]),
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
@@ -178,6 +171,7 @@ mod test {
         let mut solver = Solution::default();
         solver.with_input(get_input());
         let solution = solver.solve_part1();
+        assert_eq!(solution, "21");
     }
 
     #[test]
@@ -185,6 +179,31 @@ mod test {
         let mut solver = Solution::default();
         solver.with_input(get_input());
         let solution = solver.solve_part2();
+        assert_eq!(solution, "8");
+    }
+
+    #[test]
+    fn test_scores() {
+        let scores = Forest::parse(Input::new(get_input())).unwrap().scores();
+        assert_eq!(scores.scenic[5 + 2], 4);
+        assert_eq!(scores.scenic[3 * 5 + 2], 8);
+        assert!(!scores.visible[2 * 5 + 2]);
+        assert_eq!(
+            scores.heatmap(),
+            "\
+P2
+5 5
+255
+0 0 0 0 0
+0 31 127 31 0
+0 191 31 63 0
+0 31 255 95 0
+0 0 0 0 0
+"
+        );
+        assert!(Solution::default()
+            .set_param("heatmap", "trees.png")
+            .is_err());
     }
 }
 
@@ -1,13 +1,37 @@
-use std::{collections::HashSet, fmt::Display, primitive::f32, str::FromStr};
+use std::{fmt::Write, iter, str::FromStr};
 
-use crate::solver::Solver;
+use crate::{
+    params::{assign, ParamError},
+    parse::{complete, pattern, Input, ParseError},
+    solver::Solver,
+    utils::FxHashSet,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum Direction {
     Up,
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (1, -1),
+            Direction::DownLeft => (-1, 1),
+            Direction::DownRight => (1, 1),
+        }
+    }
 }
 
 impl FromStr for Direction {
@@ -18,6 +42,10 @@ impl FromStr for Direction {
             "D" => Ok(Direction::Down),
             "L" => Ok(Direction::Left),
             "R" => Ok(Direction::Right),
+            "UL" => Ok(Direction::UpLeft),
+            "UR" => Ok(Direction::UpRight),
+            "DL" => Ok(Direction::DownLeft),
+            "DR" => Ok(Direction::DownRight),
             _ => Err(format!("UNKNOWN DIRECTION: {s}")),
         }
     }
@@ -29,53 +57,140 @@ struct Move {
     amt: usize,
 }
 
-impl FromStr for Move {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (dir, amt) = s.split_once(' ').unwrap();
+impl Move {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let [dir, amt] = complete(pattern("{} {}"), input)?;
         Ok(Self {
             dir: dir.parse()?,
-            amt: amt.parse().map_err(|e| format!("{:?}", e))?,
+            amt: amt.parse()?,
         })
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Clone, Debug)]
+/// The single steps the head takes to make `moves`, in order.
+fn steps(moves: &[Move]) -> impl Iterator<Item = Direction> + '_ {
+    moves.iter().flat_map(|m| iter::repeat_n(m.dir, m.amt))
+}
+
+/// `(x, y)`, with `y` growing downwards.
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug, Default)]
 struct Position(isize, isize);
 
 impl Position {
-    fn move_closer_to(&mut self, other: &Position) {
-        if self.distance_to(other) >= 2.0 {
-            self.0 += (other.0 - self.0).signum();
-            self.1 += (other.1 - self.1).signum();
+    /// Knots touch, diagonals included, when this is at most 1.
+    fn chebyshev(&self, other: &Position) -> isize {
+        (self.0 - other.0).abs().max((self.1 - other.1).abs())
+    }
+
+    fn follow(&mut self, leader: &Position) {
+        if self.chebyshev(leader) >= 2 {
+            self.0 += (leader.0 - self.0).signum();
+            self.1 += (leader.1 - self.1).signum();
         }
     }
+}
+
+/// A rope of any number of knots starting on top of each other, which
+/// remembers everywhere each knot has been.
+#[derive(Debug, Clone)]
+struct Rope {
+    knots: Vec<Position>,
+    visited: Vec<FxHashSet<Position>>,
+}
 
-    fn move_by_dir(&mut self, dir: &Direction) {
-        match dir {
-            Direction::Up => self.1 += -1,
-            Direction::Down => self.1 += 1,
-            Direction::Left => self.0 += -1,
-            Direction::Right => self.0 += 1,
-        };
+impl Rope {
+    fn new(knots: usize) -> Self {
+        assert!(knots > 0, "a rope needs at least one knot");
+        let start = Position::default();
+        Self {
+            knots: vec![start; knots],
+            visited: vec![FxHashSet::from_iter([start]); knots],
+        }
     }
 
-    fn distance_to(&self, other: &Position) -> f32 {
-        f32::sqrt(
-            f32::powf((other.0 - self.0) as f32, 2.0) + f32::powf((other.1 - self.1) as f32, 2.0),
-        )
+    /// Moves the head one step and lets the rest of the rope catch up.
+    fn step(&mut self, dir: Direction) {
+        let (dx, dy) = dir.offset();
+        self.knots[0].0 += dx;
+        self.knots[0].1 += dy;
+        self.visited[0].insert(self.knots[0]);
+
+        for i in 1..self.knots.len() {
+            let leader = self.knots[i - 1];
+            if self.knots[i].chebyshev(&leader) < 2 {
+                // nothing further back can move either
+                break;
+            }
+            self.knots[i].follow(&leader);
+            self.visited[i].insert(self.knots[i]);
+        }
     }
-}
 
-impl Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}:{}", self.0, self.1)
+    fn tail_visits(&self) -> usize {
+        self.visited.last().unwrap().len()
+    }
+
+    /// Draws the rope the way the puzzle does, with `H` for the head, then
+    /// numbered knots, `s` for the start and `#` wherever the tail has been.
+    /// Knots further forward are drawn over the ones behind them.
+    fn frame(&self) -> String {
+        let trail = self.visited.last().unwrap();
+        // the trail always includes the start
+        let points = self.knots.iter().chain(trail);
+        let (min_x, max_x, min_y, max_y) = points.fold(
+            (isize::MAX, isize::MIN, isize::MAX, isize::MIN),
+            |(min_x, max_x, min_y, max_y), p| {
+                (
+                    min_x.min(p.0),
+                    max_x.max(p.0),
+                    min_y.min(p.1),
+                    max_y.max(p.1),
+                )
+            },
+        );
+
+        let mut frame = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let pos = Position(x, y);
+                let tile = match self.knots.iter().position(|k| *k == pos) {
+                    Some(0) => 'H',
+                    Some(i) => char::from_digit(i as u32, 36).unwrap_or('*'),
+                    None if pos == Position::default() => 's',
+                    None if trail.contains(&pos) => '#',
+                    None => '.',
+                };
+                frame.push(tile);
+            }
+            writeln!(frame).unwrap();
+        }
+        frame
     }
 }
 
 #[derive(Default)]
 pub struct Solution {
     input: Vec<Move>,
+    // print the rope after every step the head takes
+    frames: bool,
+    // print the rope and the tail's trail once the head has stopped
+    render: bool,
+}
+
+impl Solution {
+    fn tail_visits(&self, knots: usize) -> usize {
+        let mut rope = Rope::new(knots);
+        for dir in steps(&self.input) {
+            rope.step(dir);
+            if self.frames {
+                println!("{}", rope.frame());
+            }
+        }
+        if self.render {
+            println!("{}", rope.frame());
+        }
+        rope.tail_visits()
+    }
 }
 
 impl Solver for Solution {
@@ -84,45 +199,27 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input.lines().map(|l| l.parse().unwrap()).collect();
+        self.input = Input::new(input)
+            .lines()
+            .map(Move::parse)
+            .collect::<Result<_, _>>()
+            .unwrap();
     }
 
     fn solve_part1(&self) -> String {
-        let mut head = Position(0, 0);
-        let mut tail = Position(0, 0);
-        let mut tail_pos = HashSet::new();
-        tail_pos.insert(tail.clone());
-
-        for m in self.input.iter() {
-            for _i in 0..m.amt {
-                head.move_by_dir(&m.dir);
-                tail.move_closer_to(&head);
-                tail_pos.insert(tail.clone());
-            }
-        }
-
-        tail_pos.len().to_string()
+        self.tail_visits(2).to_string()
     }
 
     fn solve_part2(&self) -> String {
-        let rope_len = 10;
-        let mut rope = vec![Position(0, 0); rope_len];
-        let mut tail_pos = HashSet::new();
-        tail_pos.insert(rope.last().unwrap().clone());
+        self.tail_visits(10).to_string()
+    }
 
-        for m in self.input.iter() {
-            for _i in 0..m.amt {
-                rope.first_mut().unwrap().move_by_dir(&m.dir);
-                for j in 1..rope_len {
-                    let prev = rope.get(j - 1).unwrap().clone();
-                    let cur = rope.get_mut(j).unwrap();
-                    cur.move_closer_to(&prev);
-                }
-                tail_pos.insert(rope.last().unwrap().clone());
-            }
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "frames" => assign(&mut self.frames, name, value),
+            "render" => assign(&mut self.render, name, value),
+            _ => Err(ParamError::unknown(name)),
         }
-
-        tail_pos.len().to_string()
     }
 }
 
@@ -168,6 +265,32 @@ U 20"
         let solution = solver.solve_part2();
         assert_eq!(solution, "36");
     }
+
+    #[test]
+    fn test_frames() {
+        let moves = [Move::parse(Input::new("R 4")).unwrap()];
+        let mut rope = Rope::new(10);
+        let frames = steps(&moves)
+            .map(|dir| {
+                rope.step(dir);
+                rope.frame()
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(frames[0], "1H\n");
+        assert_eq!(frames[3], "4321H\n");
+
+        // a diagonal head drags the whole rope along its line
+        let mut rope = Rope::new(3);
+        rope.step(Direction::UpRight);
+        rope.step(Direction::UpRight);
+        assert_eq!(rope.frame(), "..H\n.1.\n2..\n");
+        rope.step(Direction::UpRight);
+        assert_eq!(rope.frame(), "...H\n..1.\n.2..\ns...\n");
+        assert_eq!(
+            rope.visited.iter().map(|v| v.len()).collect::<Vec<_>>(),
+            [4, 3, 2]
+        );
+    }
 }
 
 const INPUT: &str = "R 2
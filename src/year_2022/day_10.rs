@@ -1,108 +1,87 @@
-use std::str::FromStr;
+use crate::{
+    emulator::{Cpu, Instruction, Register, Tick},
+    params::{assign, ParamError},
+    parse::Input,
+    solver::Solver,
+};
 
-use crate::solver::Solver;
+const SCREEN_WIDTH: usize = 40;
 
-#[derive(Debug)]
-enum Command {
-    Noop,
-    Addx(isize),
+fn x(tick: &Tick) -> i64 {
+    tick.register(Register::new('x').unwrap())
 }
 
-impl FromStr for Command {
-    type Err = String;
+#[derive(Default)]
+pub struct Solution {
+    program: Vec<Instruction>,
+    trace: bool,
+    stop_at: Option<usize>,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s == "noop" {
-            Ok(Self::Noop)
-        } else {
-            let (_, amt) = s.split_once(' ').ok_or_else(|| "bad command".to_string())?;
-            let amt = amt
-                .parse()
-                .map_err(|_| "failed to parse addx number".to_string())?;
-            Ok(Self::Addx(amt))
+impl Solution {
+    /// Runs the program with `x` starting at 1, calling `hook` every cycle.
+    fn run(&self, hook: impl FnMut(&Tick)) {
+        let mut cpu = Cpu::new(self.program.clone());
+        cpu.set_register(Register::new('x').unwrap(), 1);
+        cpu.add_hook(hook);
+        if self.trace {
+            cpu.trace();
+        }
+        if let Some(cycle) = self.stop_at {
+            cpu.stop_at(cycle);
         }
+        cpu.run();
     }
 }
 
-#[derive(Debug, Clone)]
-struct CPUState {
-    register: isize,
-    add: Option<isize>,
-}
-
-#[derive(Default)]
-pub struct Solution {
-    input: Vec<CPUState>,
-}
-
 impl Solver for Solution {
     fn get_input(&self) -> &'static str {
         INPUT
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input
+        self.program = Input::new(input)
             .lines()
-            .map(|l| l.parse().unwrap())
-            .flat_map(|c| match c {
-                Command::Noop => vec![None],
-                Command::Addx(amt) => vec![None, Some(amt)],
-            })
-            .scan(
-                CPUState {
-                    register: 1,
-                    add: None,
-                },
-                |cpu, add| {
-                    // this add amount is from the previous cycle
-                    // so we do it here which is equivelent to doing
-                    // it at the end of the previous cycle
-                    if let Some(amt) = cpu.add {
-                        cpu.register += amt;
-                    }
-                    // queue up the next possible add
-                    cpu.add = add;
-                    Some(cpu.clone())
-                },
-            )
-            .collect();
+            .map(Instruction::decode)
+            .collect::<Result<_, _>>()
+            .unwrap();
     }
 
     fn solve_part1(&self) -> String {
-        let signal_strength = self
-            .input
-            .iter()
-            .skip(19) // we skip 19 because the number of cycles starts at 1
-            .step_by(40)
-            .enumerate()
-            .fold(0_isize, |acc, (i, cpu)| {
-                acc + (((i as isize * 40) + 20) * cpu.register)
-            });
-
+        let mut signal_strength = 0;
+        self.run(|tick| {
+            if tick.cycle % SCREEN_WIDTH == 20 {
+                signal_strength += tick.cycle as i64 * x(tick);
+            }
+        });
         signal_strength.to_string()
     }
 
     fn solve_part2(&self) -> String {
-        let signal_strength = self
-            .input
-            .chunks(40)
-            .map(|chunk| {
-                chunk
-                    .iter()
-                    .enumerate()
-                    .map(|(i, cpu)| {
-                        let r = cpu.register;
-                        if i as isize >= r - 1 && i as isize <= r + 1 {
-                            "#"
-                        } else {
-                            "."
-                        }
-                    })
-                    .collect::<String>()
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
-        format!("\n\n{signal_strength}\n")
+        let mut pixels = String::new();
+        self.run(|tick| {
+            let col = (tick.cycle - 1) % SCREEN_WIDTH;
+            if col == 0 && tick.cycle > 1 {
+                pixels.push('\n');
+            }
+            // the sprite is three pixels wide, centred on `x`
+            let lit = (x(tick) - col as i64).abs() <= 1;
+            pixels.push(if lit { '#' } else { '.' });
+        });
+        format!("\n\n{pixels}\n")
+    }
+
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "trace" => assign(&mut self.trace, name, value),
+            "stop_at" => {
+                let mut cycle = 0;
+                assign(&mut cycle, name, value)?;
+                self.stop_at = Some(cycle);
+                Ok(())
+            }
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
@@ -1,109 +1,164 @@
-use std::{cell::RefCell, str::FromStr};
+use crate::{
+    expr::BinOp,
+    math::{lcm_all, mul_mod},
+    params::{assign, ParamError},
+    parse::{
+        complete, literal, one_of, pattern, preceded, separated, take_while, terminated, token,
+        unsigned, Input, ParseError, ParseResult, Parser,
+    },
+    solver::Solver,
+};
+
+/// The right hand side of a monkey's `new = ...` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Old,
+    Const(u64),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
 
-use crate::solver::Solver;
+impl Expr {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        complete(terminated(expr, take_while(char::is_whitespace)), input)
+    }
 
-#[derive(Debug, Clone)]
-enum Op {
-    Sum,
-    Product,
-}
+    /// Works out the new worry level, keeping it below `modulus` if there is
+    /// one. Fails on overflow, on going negative or dividing by zero, and on
+    /// any division at all under a modulus, which it can't be mixed with.
+    fn eval(&self, old: u64, modulus: Option<u64>) -> Option<u64> {
+        // `old` is always below the modulus already, and so are most
+        // constants, so only reduce when needed
+        let reduce = |value: u64| match modulus {
+            Some(m) if value >= m => value % m,
+            _ => value,
+        };
+        match self {
+            Expr::Old => Some(old),
+            Expr::Const(c) => Some(reduce(*c)),
+            Expr::Binary(op, left, right) => {
+                let (a, b) = (left.eval(old, modulus)?, right.eval(old, modulus)?);
+                // under a modulus both sides are already below it
+                let value = match (op, modulus) {
+                    (BinOp::Add, None) => a.checked_add(b)?,
+                    (BinOp::Add, Some(m)) => ((a as u128 + b as u128) % m as u128) as u64,
+                    (BinOp::Mul, None) => a.checked_mul(b)?,
+                    (BinOp::Mul, Some(m)) => mul_mod(a, b, m),
+                    (BinOp::Sub, None) => a.checked_sub(b)?,
+                    (BinOp::Sub, Some(m)) => a.checked_sub(b).unwrap_or_else(|| a + (m - b)),
+                    (BinOp::Div, None) => a.checked_div(b)?,
+                    (BinOp::Div, Some(_)) => return None,
+                };
+                Some(value)
+            }
+        }
+    }
 
-#[derive(Debug, Clone)]
-enum Target {
-    Me,
-    Amount(usize),
+    fn has_division(&self) -> bool {
+        match self {
+            Expr::Old | Expr::Const(_) => false,
+            Expr::Binary(op, left, right) => {
+                *op == BinOp::Div || left.has_division() || right.has_division()
+            }
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-struct Operation {
-    op: Op,
-    target: Target,
+// expr = term (("+" | "-") term)*
+fn expr(input: Input) -> ParseResult<Expr> {
+    chain(input, &["+", "-"], term)
 }
 
-impl Operation {
-    fn apply(&self, old: usize) -> usize {
-        let amount = match self.target {
-            Target::Me => old,
-            Target::Amount(amt) => amt,
-        };
+// term = factor (("*" | "/") factor)*
+fn term(input: Input) -> ParseResult<Expr> {
+    chain(input, &["*", "/"], factor)
+}
 
-        match self.op {
-            Op::Sum => old + amount,
-            Op::Product => old * amount,
-        }
+// factor = "old" | number | "(" expr ")"
+fn factor(input: Input) -> ParseResult<Expr> {
+    let (_, input) = take_while(char::is_whitespace).parse(input)?;
+    if let Ok((_, rest)) = literal("(").parse(input) {
+        terminated(expr, token(literal(")"))).parse(rest)
+    } else if let Ok((_, rest)) = literal("old").parse(input) {
+        Ok((Expr::Old, rest))
+    } else {
+        unsigned()
+            .map(Expr::Const)
+            .parse(input)
+            .map_err(|_| input.error("expected `old`, a number or `(`"))
     }
 }
 
-impl FromStr for Operation {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (op, amt) = s.split_once(' ').unwrap();
-        let op = if op == "+" { Op::Sum } else { Op::Product };
-        let target = if amt == "old" {
-            Target::Me
-        } else {
-            Target::Amount(amt.parse().unwrap())
-        };
-        Ok(Self { op, target })
+/// Left-associative runs of `operand` joined by any of `ops`.
+fn chain<'a>(
+    input: Input<'a>,
+    ops: &[&str],
+    operand: fn(Input<'a>) -> ParseResult<'a, Expr>,
+) -> ParseResult<'a, Expr> {
+    let (mut left, mut input) = operand(input)?;
+    while let Ok((op, rest)) = token(one_of(ops)).parse(input) {
+        let (right, rest) = operand(rest)?;
+        let op = BinOp::from_symbol(op).unwrap();
+        left = Expr::Binary(op, Box::new(left), Box::new(right));
+        input = rest;
     }
+    Ok((left, input))
 }
 
 #[derive(Debug, Clone)]
 struct Monkey {
-    index: usize,
-    items: RefCell<Vec<usize>>,
-    operation: Operation,
-    test_divisible: usize,
+    items: Vec<u64>,
+    operation: Expr,
+    test_divisible: u64,
     test_true_to_monkey: usize,
     test_false_to_monkey: usize,
 }
 
-impl FromStr for Monkey {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let lines = s.trim().lines().collect::<Vec<&str>>();
-
-        let index = lines[0]
-            .trim()
-            .trim_start_matches("Monkey ")
-            .trim_end_matches(':')
-            .parse()
-            .map_err(|_| "bad monkey index".to_string())?;
-
-        let items = lines[1]
-            .trim()
-            .trim_start_matches("Starting items: ")
-            .split(", ")
-            .map(|i| i.parse::<usize>().map_err(|_| "bad item".to_string()))
-            .collect::<Result<Vec<usize>, String>>()?;
-
-        let operation = lines[2]
-            .trim()
-            .trim_start_matches("Operation: new = old ")
-            .parse()
-            .unwrap();
-
-        let test_divisible = lines[3]
-            .trim()
-            .trim_start_matches("Test: divisible by ")
-            .parse()
-            .unwrap();
-
-        let test_true_to_monkey = lines[4]
-            .trim()
-            .trim_start_matches("If true: throw to monkey ")
-            .parse()
-            .unwrap();
-
-        let test_false_to_monkey = lines[5]
-            .trim()
-            .trim_start_matches("If false: throw to monkey ")
-            .parse()
-            .unwrap();
+impl Monkey {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let lines = input.lines().map(Input::trim).collect::<Vec<Input>>();
+        let line = |i: usize| {
+            lines
+                .get(i)
+                .copied()
+                .ok_or_else(|| input.error(format!("monkey is missing line {}", i + 1)))
+        };
+
+        complete(
+            preceded(
+                literal("Monkey "),
+                terminated(unsigned::<usize>(), literal(":")),
+            ),
+            line(0)?,
+        )?;
+
+        let items = complete(
+            preceded(
+                literal("Starting items: "),
+                separated(unsigned(), literal(", ")),
+            ),
+            line(1)?,
+        )?;
+
+        let [operation_text] = complete(pattern("Operation: new = {}"), line(2)?)?;
+        let operation = Expr::parse(operation_text)?;
+
+        let test_divisible = complete(
+            preceded(literal("Test: divisible by "), unsigned()),
+            line(3)?,
+        )?;
+
+        let test_true_to_monkey = complete(
+            preceded(literal("If true: throw to monkey "), unsigned()),
+            line(4)?,
+        )?;
+
+        let test_false_to_monkey = complete(
+            preceded(literal("If false: throw to monkey "), unsigned()),
+            line(5)?,
+        )?;
 
         Ok(Self {
-            index,
-            items: RefCell::new(items),
+            items,
             operation,
             test_divisible,
             test_true_to_monkey,
@@ -112,9 +167,175 @@ impl FromStr for Monkey {
     }
 }
 
-#[derive(Default, Clone)]
+/// Parses every monkey, checking that each throws only to monkeys that
+/// exist.
+fn parse_monkeys(input: Input) -> Result<Vec<Monkey>, ParseError> {
+    let blocks = input.blocks().collect::<Vec<Input>>();
+    let monkeys = blocks
+        .iter()
+        .map(|block| Monkey::parse(*block))
+        .collect::<Result<Vec<Monkey>, ParseError>>()?;
+    for (block, monkey) in blocks.iter().zip(&monkeys) {
+        let targets = [monkey.test_true_to_monkey, monkey.test_false_to_monkey];
+        for (line, target) in block.lines().skip(4).zip(targets) {
+            if target >= monkeys.len() {
+                return Err(line.trim().error(format!("there is no monkey {target}")));
+            }
+        }
+    }
+    Ok(monkeys)
+}
+
+/// What happened in one round of keep away.
+#[derive(Debug, Clone)]
+struct Round {
+    /// How many items each monkey inspected this round.
+    inspections: Vec<u64>,
+    /// Which monkey holds each item at the end of the round, with items
+    /// numbered in the order they were first listed.
+    holders: Vec<usize>,
+}
+
+impl Round {
+    /// How many items each monkey holds at the end of the round.
+    fn held(&self) -> Vec<usize> {
+        let mut held = vec![0; self.inspections.len()];
+        self.holders.iter().for_each(|monkey| held[*monkey] += 1);
+        held
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct History {
+    rounds: Vec<Round>,
+}
+
+impl History {
+    /// How many items each monkey inspected over the first `rounds` rounds.
+    fn inspections(&self, rounds: usize) -> Vec<u64> {
+        let monkeys = self.rounds.first().map_or(0, |r| r.inspections.len());
+        self.rounds
+            .iter()
+            .take(rounds)
+            .fold(vec![0; monkeys], |mut totals, round| {
+                totals
+                    .iter_mut()
+                    .zip(&round.inspections)
+                    .for_each(|(total, n)| *total += n);
+                totals
+            })
+    }
+
+    /// The product of the `k` highest inspection counts over every round.
+    fn monkey_business(&self, k: usize) -> u64 {
+        let mut totals = self.inspections(self.rounds.len());
+        totals.sort_unstable_by(|a, b| b.cmp(a));
+        totals.iter().take(k).product()
+    }
+}
+
+/// Plays `rounds` rounds, dividing worry by `relief` after each inspection
+/// and keeping it below `modulus` if there is one. Gives up if any monkey's
+/// operation can't be worked out, or divides when there's a modulus.
+fn simulate(
+    monkeys: &[Monkey],
+    rounds: usize,
+    relief: u64,
+    modulus: Option<u64>,
+) -> Result<History, String> {
+    if let Some(m) = modulus {
+        // division doesn't survive reducing worry modulo the divisors' lcm
+        if let Some(i) = monkeys.iter().position(|m| m.operation.has_division()) {
+            return Err(format!(
+                "monkey {i}'s operation divides, which can't be done modulo {m}"
+            ));
+        }
+    }
+    // (item number, worry) for everything each monkey is holding
+    let mut held = Vec::with_capacity(monkeys.len());
+    let mut item_count = 0;
+    for m in monkeys {
+        let items = m.items.iter().map(|w| {
+            item_count += 1;
+            (item_count - 1, *w)
+        });
+        held.push(items.collect::<Vec<(usize, u64)>>());
+    }
+
+    let mut history = History::default();
+    // swapped with each monkey's items in turn so no buffer is ever freed
+    let mut turn = Vec::new();
+    for _ in 0..rounds {
+        let mut inspections = vec![0; monkeys.len()];
+        for (i, m) in monkeys.iter().enumerate() {
+            std::mem::swap(&mut turn, &mut held[i]);
+            for (item, worry) in turn.drain(..) {
+                inspections[i] += 1;
+                let mut worry = m.operation.eval(worry, modulus).ok_or_else(|| {
+                    format!("monkey {i} can't work out a new worry level from {worry}")
+                })?;
+                if relief > 1 {
+                    worry /= relief;
+                }
+                let target = if worry.is_multiple_of(m.test_divisible) {
+                    m.test_true_to_monkey
+                } else {
+                    m.test_false_to_monkey
+                };
+                held[target].push((item, worry));
+            }
+        }
+
+        let mut holders = vec![0; item_count];
+        for (monkey, items) in held.iter().enumerate() {
+            items.iter().for_each(|(item, _)| holders[*item] = monkey);
+        }
+        history.rounds.push(Round {
+            inspections,
+            holders,
+        });
+    }
+    Ok(history)
+}
+
+#[derive(Clone)]
 pub struct Solution {
     input: Vec<Monkey>,
+    part1_rounds: usize,
+    part2_rounds: usize,
+    // print what each monkey inspected and held after every round
+    stats: bool,
+}
+
+impl Default for Solution {
+    fn default() -> Self {
+        Self {
+            input: Vec::new(),
+            part1_rounds: 20,
+            part2_rounds: 10000,
+            stats: false,
+        }
+    }
+}
+
+impl Solution {
+    fn monkey_business(&self, rounds: usize, relief: u64, modulus: Option<u64>) -> String {
+        let history = match simulate(&self.input, rounds, relief, modulus) {
+            Ok(history) => history,
+            Err(e) => return e,
+        };
+        if self.stats {
+            for (i, round) in history.rounds.iter().enumerate() {
+                println!(
+                    "round {:>5}: inspected {:?}, holding {:?}",
+                    i + 1,
+                    round.inspections,
+                    round.held()
+                );
+            }
+        }
+        history.monkey_business(2).to_string()
+    }
 }
 
 impl Solver for Solution {
@@ -123,75 +344,28 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input
-            .split("\n\n")
-            .map(|c| c.parse::<Monkey>().unwrap())
-            .collect();
+        self.input = parse_monkeys(Input::new(input)).unwrap();
     }
 
     fn solve_part1(&self) -> String {
-        let monkeys = self.input.clone();
-        let mut monkey_business = vec![0; monkeys.len()];
-        let num_roundds = 20;
-        for _ in 0..num_roundds {
-            for m in monkeys.iter() {
-                for i in m.items.borrow_mut().drain(0..) {
-                    monkey_business[m.index] += 1;
-                    let worry = m.operation.apply(i) / 3;
-                    if worry % m.test_divisible == 0 {
-                        monkeys[m.test_true_to_monkey]
-                            .items
-                            .borrow_mut()
-                            .push(worry);
-                    } else {
-                        monkeys[m.test_false_to_monkey]
-                            .items
-                            .borrow_mut()
-                            .push(worry);
-                    }
-                }
-            }
-        }
-
-        monkey_business.sort();
-        monkey_business.reverse();
-        let solution = monkey_business[0] * monkey_business[1];
-        solution.to_string()
+        self.monkey_business(self.part1_rounds, 3, None)
     }
 
     fn solve_part2(&self) -> String {
-        let monkeys = self.input.clone();
-
-        // get common denominator for all monkey divisors so we
-        // can use the modulo of this LCD to keep worry low
-        let common_denom: usize = monkeys.iter().map(|m| m.test_divisible).product();
-
-        let mut monkey_business = vec![0_u64; monkeys.len()];
-        let num_roundds = 10000;
-        for _ in 0..num_roundds {
-            for m in monkeys.iter() {
-                for i in m.items.borrow_mut().drain(0..) {
-                    monkey_business[m.index] += 1;
-                    let worry = m.operation.apply(i) % common_denom;
-                    if worry % m.test_divisible == 0 {
-                        monkeys[m.test_true_to_monkey]
-                            .items
-                            .borrow_mut()
-                            .push(worry);
-                    } else {
-                        monkeys[m.test_false_to_monkey]
-                            .items
-                            .borrow_mut()
-                            .push(worry);
-                    }
-                }
-            }
-        }
+        // every divisibility test still gives the same answer modulo the
+        // lcm of all the divisors, so we can use it to keep worry low
+        let common_denom = lcm_all(self.input.iter().map(|m| m.test_divisible));
+
+        self.monkey_business(self.part2_rounds, 1, Some(common_denom))
+    }
 
-        monkey_business.sort();
-        monkey_business.reverse();
-        let solution = monkey_business[0] * monkey_business[1];
-        solution.to_string()
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "part1_rounds" => assign(&mut self.part1_rounds, name, value),
+            "part2_rounds" => assign(&mut self.part2_rounds, name, value),
+            "stats" => assign(&mut self.stats, name, value),
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
@@ -245,6 +419,79 @@ Test: divisible by 17
         let solution = solver.solve_part2();
         assert_eq!(solution, "2713310158");
     }
+
+    #[test]
+    fn test_expressions() {
+        let eval = |s: &str, old| Expr::parse(Input::new(s)).unwrap().eval(old, None);
+        assert_eq!(eval("old * 19", 2), Some(38));
+        assert_eq!(eval("old*old + 3", 4), Some(19));
+        assert_eq!(eval("2 * (old + 3) - old / 2", 10), Some(21));
+        assert_eq!(eval("old - 5 - 1", 10), Some(4));
+        assert_eq!(eval("old - 11", 10), None);
+        assert_eq!(
+            Expr::parse(Input::new("old - 11"))
+                .unwrap()
+                .eval(10, Some(7)),
+            Some(6)
+        );
+
+        // a modulus past 2^32 mustn't overflow products of in-range values
+        let modular = |s: &str, old| {
+            Expr::parse(Input::new(s))
+                .unwrap()
+                .eval(old, Some(5_000_000_011))
+        };
+        assert_eq!(modular("old * old", 3_000_000_017), Some(2_200_000_113));
+        assert_eq!(modular("old * 19 + 7", 3_000_000_017), Some(2_000_000_209));
+        assert_eq!(
+            modular("old - 5000000010", 3_000_000_017),
+            Some(3_000_000_018)
+        );
+
+        assert_eq!(Expr::parse(Input::new("old ^ 2")).unwrap_err().column, 5);
+        assert_eq!(Expr::parse(Input::new("(old + 1")).unwrap_err().column, 9);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut solver = Solution::default();
+        solver.with_input(get_input());
+        let history = simulate(&solver.input, 20, 3, None).unwrap();
+        assert_eq!(history.inspections(20), [101, 95, 7, 105]);
+        assert_eq!(history.rounds[0].inspections, [2, 4, 3, 5]);
+        // after the first round monkey 0 holds four items, monkey 1 the rest
+        let holders = &history.rounds[0].holders;
+        assert_eq!(holders.iter().filter(|m| **m == 0).count(), 4);
+        assert_eq!(holders.iter().filter(|m| **m == 1).count(), 6);
+        assert_eq!(history.rounds[0].held(), [4, 6, 0, 0]);
+        assert_eq!(history.monkey_business(3), 105 * 101 * 95);
+
+        // without relief or a modulus worry soon overflows
+        assert_eq!(
+            simulate(&solver.input, 20, 1, None).unwrap_err(),
+            "monkey 0 can't work out a new worry level from 13988703546165100909"
+        );
+    }
+
+    #[test]
+    fn test_division() {
+        // part 1 can divide, but part 2's modulus can't
+        let mut solver = Solution::default();
+        solver.with_input(&get_input().replace("old * 19", "old * 38 / 2"));
+        assert_eq!(solver.solve_part1(), "10605");
+        assert_eq!(
+            solver.solve_part2(),
+            "monkey 0's operation divides, which can't be done modulo 96577"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let lost = get_input().replace("throw to monkey 3", "throw to monkey 4");
+        let error = parse_monkeys(Input::new(&lost)).unwrap_err();
+        assert_eq!(error.line, 6);
+        assert_eq!(error.message, "there is no monkey 4");
+    }
 }
 
 const INPUT: &str = "Monkey 0:
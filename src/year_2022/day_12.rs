@@ -1,133 +1,177 @@
-use std::{
-    collections::{BinaryHeap, HashMap},
-    fmt::Display,
-    hash::Hash,
+use std::fmt::Write;
+
+use crate::{
+    params::{assign, assign_file, ParamError},
+    parse::{Input, ParseError},
+    search::bfs_distances,
+    solver::Solver,
+    utils::{write_output, FxHashMap},
 };
 
-use crate::solver::Solver;
-
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
-struct Position(i16, i16);
-
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
-struct Point {
-    position: Position,
-    height: u8,
-}
+/// `(row, column)`
+type Position = (usize, usize);
 
 #[derive(Default, Debug)]
-struct Grid(Vec<Vec<Point>>);
+struct Heightmap {
+    // row by row, 0 for `a` up to 25 for `z`
+    heights: Vec<u8>,
+    width: usize,
+    start: Position,
+    end: Position,
+}
 
-impl Grid {
-    fn get(&self, pos: Position) -> Option<&Point> {
-        if pos.0 < 0 || pos.1 < 0 {
-            return None;
+impl Heightmap {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let mut map = Self::default();
+        let (mut start, mut end) = (None, None);
+        for (row, line) in input.lines().enumerate() {
+            if row > 0 && line.as_str().len() != map.width {
+                return Err(line.error(format!("expected a row {} wide", map.width)));
+            }
+            map.width = line.as_str().len();
+            for (col, c) in line.chars().enumerate() {
+                match c {
+                    'S' => start = Some((row, col)),
+                    'E' => end = Some((row, col)),
+                    _ => {}
+                }
+                map.heights.push(match c {
+                    'S' => 0,
+                    'E' => 25,
+                    'a'..='z' => c as u8 - b'a',
+                    c => return Err(line.advance(col).error(format!("invalid height `{c}`"))),
+                });
+            }
         }
-
-        self.0.get(pos.1 as usize)?.get(pos.0 as usize)
+        map.start = start.ok_or_else(|| input.error("no start `S`"))?;
+        map.end = end.ok_or_else(|| input.error("no end `E`"))?;
+        Ok(map)
     }
 
-    fn neighbors(&self, cur: Position) -> Vec<Position> {
-        if let Some(Point {
-            position: Position(x, y),
-            height,
-        }) = self.get(cur)
-        {
-            [(*x, *y - 1), (*x, *y + 1), (*x - 1, *y), (*x + 1, *y)]
-                .iter()
-                .filter_map(|(x, y)| {
-                    self.get(Position(*x, *y))
-                        .filter(|p| p.height as i16 - *height as i16 <= 1)
-                        .map(|p| p.position)
-                })
-                .collect()
-        } else {
-            Vec::new()
-        }
+    fn rows(&self) -> usize {
+        self.heights.len() / self.width.max(1)
     }
 
-    fn heuristic(&self, a: Position, b: Position) -> i16 {
-        let Position(x1, y1) = a;
-        let Position(x2, y2) = b;
-        (x1 - x2).abs() + (y1 - y2).abs()
+    fn height(&self, (row, col): Position) -> u8 {
+        self.heights[row * self.width + col]
     }
 
-    fn astar(&self, start: &[Position], end: Position) -> Vec<Position> {
-        let mut frontier: BinaryHeap<WeightedPosition> =
-            BinaryHeap::from_iter(start.iter().map(|position| WeightedPosition {
-                position: *position,
-                weight: 0,
-            }));
-        let mut came_from: HashMap<Position, Option<Position>> =
-            start.iter().map(|start| (*start, None)).collect();
-        let mut cost_so_far: HashMap<Position, i16> =
-            start.iter().map(|start| (*start, 0i16)).collect();
+    fn neighbours(&self, (row, col): Position) -> impl Iterator<Item = Position> + '_ {
+        [
+            (row.wrapping_sub(1), col),
+            (row + 1, col),
+            (row, col.wrapping_sub(1)),
+            (row, col + 1),
+        ]
+        .into_iter()
+        .filter(|(r, c)| *r < self.rows() && *c < self.width)
+    }
 
-        while let Some(current) = frontier.pop() {
-            if current.position == end {
-                break;
-            }
+    /// The step from `from` to `to` is at most one up, though it can drop
+    /// any distance.
+    fn can_climb(&self, from: Position, to: Position) -> bool {
+        self.height(to) <= self.height(from) + 1
+    }
 
-            for next in self.neighbors(current.position) {
-                let new_cost = cost_so_far.get(&current.position).unwrap().to_owned() + 1;
-                if !cost_so_far.contains_key(&next) || new_cost < *cost_so_far.get(&next).unwrap() {
-                    cost_so_far
-                        .entry(next)
-                        .and_modify(|e| *e = new_cost)
-                        .or_insert(new_cost);
+    /// The fewest steps from every square that can reach the end, found by
+    /// searching backwards from the end.
+    fn distances_to_end(&self) -> FxHashMap<Position, usize> {
+        bfs_distances([self.end], |&pos| {
+            self.neighbours(pos)
+                .filter(move |prev| self.can_climb(*prev, pos))
+        })
+    }
 
-                    let priority = new_cost + self.heuristic(next, end);
-                    frontier.push(WeightedPosition {
-                        position: next,
-                        weight: priority,
-                    });
-                    came_from.insert(next, Some(current.position));
-                }
-            }
+    /// A shortest path from `from` to the end, both included, following the
+    /// distances downhill.
+    fn path(
+        &self,
+        from: Position,
+        distances: &FxHashMap<Position, usize>,
+    ) -> Option<Vec<Position>> {
+        let mut path = vec![from];
+        let mut distance = *distances.get(&from)?;
+        while distance > 0 {
+            let here = *path.last().unwrap();
+            let next = self.neighbours(here).find(|next| {
+                self.can_climb(here, *next) && distances.get(next) == Some(&(distance - 1))
+            })?;
+            path.push(next);
+            distance -= 1;
         }
+        Some(path)
+    }
 
-        let mut path = Vec::new();
-        if !came_from.contains_key(&end) {
-            return path;
+    /// Draws `path` over the heightmap, with an arrow on each square showing
+    /// which way the path leaves it.
+    fn render(&self, path: &[Position]) -> String {
+        let mut tiles = self
+            .heights
+            .iter()
+            .map(|h| (b'a' + h) as char)
+            .collect::<Vec<char>>();
+        tiles[self.start.0 * self.width + self.start.1] = 'S';
+        tiles[self.end.0 * self.width + self.end.1] = 'E';
+        for step in path.windows(2) {
+            let ((r1, c1), (r2, c2)) = (step[0], step[1]);
+            tiles[r1 * self.width + c1] =
+                match (r2 as isize - r1 as isize, c2 as isize - c1 as isize) {
+                    (-1, _) => '^',
+                    (1, _) => 'v',
+                    (_, -1) => '<',
+                    _ => '>',
+                };
         }
 
-        let mut current = end;
-        while !start.contains(&current) {
-            path.push(current);
-            if let Some(Some(pos)) = came_from.get(&current) {
-                current = *pos;
-            }
+        let mut out = String::new();
+        for row in tiles.chunks(self.width) {
+            writeln!(out, "{}", row.iter().collect::<String>()).unwrap();
         }
-
-        path.reverse();
-        path
-    }
-}
-
-#[derive(PartialEq, Eq)]
-struct WeightedPosition {
-    position: Position,
-    weight: i16,
-}
-
-impl Ord for WeightedPosition {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.weight.cmp(&other.weight).reverse()
+        out
     }
-}
 
-impl PartialOrd for WeightedPosition {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.weight.partial_cmp(&other.weight).map(|c| c.reverse())
+    /// Exports the heightmap as a plain PPM image, grey from low to high,
+    /// with `path` in red.
+    fn render_ppm(&self, path: &[Position]) -> String {
+        let mut out = format!("P3\n{} {}\n255\n", self.width, self.rows());
+        for row in 0..self.rows() {
+            let pixels = (0..self.width)
+                .map(|col| {
+                    if path.contains(&(row, col)) {
+                        "255 0 0".to_string()
+                    } else {
+                        let grey = self.height((row, col)) as usize * 255 / 25;
+                        format!("{grey} {grey} {grey}")
+                    }
+                })
+                .collect::<Vec<String>>();
+            writeln!(out, "{}", pixels.join(" ")).unwrap();
+        }
+        out
     }
 }
 
 #[derive(Default, Debug)]
 pub struct Solution {
-    grid: Grid,
-    start: Position,
-    alt_starts: Vec<Position>,
-    end: Position,
+    map: Heightmap,
+    distances: FxHashMap<Position, usize>,
+    // print each part's path over the heightmap
+    render: bool,
+    // write part 1's path over the heightmap to this file as a PPM image
+    ppm: String,
+}
+
+impl Solution {
+    fn steps_from(&self, from: Option<Position>) -> String {
+        let Some(from) = from.filter(|pos| self.distances.contains_key(pos)) else {
+            return "no path".to_string();
+        };
+        if self.render {
+            let path = self.map.path(from, &self.distances).unwrap();
+            println!("{}", self.map.render(&path));
+        }
+        self.distances[&from].to_string()
+    }
 }
 
 impl Solver for Solution {
@@ -136,61 +180,35 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.grid = Grid(
-            input
-                .lines()
-                .enumerate()
-                .map(|(y, l)| {
-                    l.chars()
-                        .enumerate()
-                        .map(|(x, c)| {
-                            let mut p = Point {
-                                position: Position(x as i16, y as i16),
-                                height: c as u8,
-                            };
-                            if c == 'a' {
-                                self.alt_starts.push(p.position);
-                            }
-                            if c == 'S' {
-                                self.alt_starts.push(p.position);
-                                self.start = p.position;
-                                p.height = 0;
-                            } else if c == 'E' {
-                                self.end = p.position;
-                                p.height = 25;
-                            } else {
-                                p.height -= 97;
-                            }
-                            p
-                        })
-                        .collect()
-                })
-                .collect(),
-        );
+        self.map = Heightmap::parse(Input::new(input)).unwrap();
+        self.distances = self.map.distances_to_end();
     }
 
     fn solve_part1(&self) -> String {
-        self.grid.astar(&[self.start], self.end).len().to_string()
+        if !self.ppm.is_empty() {
+            if let Some(path) = self.map.path(self.map.start, &self.distances) {
+                write_output(&self.ppm, &self.map.render_ppm(&path));
+            }
+        }
+        self.steps_from(Some(self.map.start))
     }
 
     fn solve_part2(&self) -> String {
-        self.grid
-            .astar(&self.alt_starts, self.end)
-            .len()
-            .to_string()
+        let trailhead = self
+            .distances
+            .iter()
+            .filter(|(pos, _)| self.map.height(**pos) == 0)
+            .min_by_key(|(_, d)| **d)
+            .map(|(pos, _)| *pos);
+        self.steps_from(trailhead)
     }
-}
-
-impl Display for Solution {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.grid.0.iter().for_each(|r| {
-            r.iter().for_each(|p| {
-                write!(f, " {:0>2} ", p.height).unwrap();
-            });
-            writeln!(f).unwrap();
-        });
 
-        Ok(())
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "render" => assign(&mut self.render, name, value),
+            "ppm" => assign_file(&mut self.ppm, name, value, &["ppm"]),
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
@@ -223,6 +241,35 @@ abdefghi"
         assert_eq!(solution, "29");
     }
 
+    #[test]
+    fn test_render_path() {
+        let map = Heightmap::parse(Input::new(get_input())).unwrap();
+        let distances = map.distances_to_end();
+        let path = map.path(map.start, &distances).unwrap();
+        assert_eq!(path.len(), 32);
+        assert_eq!(
+            map.render(&path),
+            "\
+vabv<<<<
+>vcvv<<^
+avcv>E^^
+a>v>>>^^
+ab>>>>>^
+"
+        );
+        assert!(map
+            .render_ppm(&path)
+            .starts_with("P3\n8 5\n255\n255 0 0 0 0 0 10 10 10"));
+    }
+
+    #[test]
+    fn test_no_path() {
+        let mut solver = Solution::default();
+        solver.with_input("SbE\nzzz");
+        assert_eq!(solver.solve_part1(), "no path");
+        assert_eq!(solver.solve_part2(), "no path");
+    }
+
     #[test]
     fn test_solution_part1() {
         let mut solver = Solution::default();
@@ -1,114 +1,129 @@
-use crate::solver::Solver;
-use std::{cmp::Ordering, vec::Drain};
-
-#[derive(PartialEq, Eq)]
-enum Token {
-    Start,
-    End,
-    Value(u8),
+use std::{cmp::Ordering, fmt::Display, slice, str::FromStr};
+
+use crate::{
+    parse::{
+        complete, literal, separated, take_while, token, Input, ParseError, ParseResult, Parser,
+    },
+    solver::Solver,
+};
+
+/// A non-negative integer of any size, kept as its decimal digits with no
+/// leading zeros so that it can be compared without converting it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Integer(String);
+
+impl Integer {
+    fn new(digits: &str) -> Self {
+        match digits.trim_start_matches('0') {
+            "" => Self("0".to_string()),
+            digits => Self(digits.to_string()),
+        }
+    }
 }
 
-struct PacketTokensIter {
-    packet: Vec<char>,
-    pos: usize,
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // more digits means bigger, otherwise digits compare like the number
+        (self.0.len(), &self.0).cmp(&(other.0.len(), &other.0))
+    }
 }
 
-impl From<&str> for PacketTokensIter {
-    fn from(s: &str) -> Self {
-        PacketTokensIter {
-            packet: s.replace("10", "a").chars().collect(),
-            pos: 0,
-        }
+impl PartialOrd for Integer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl Iterator for PacketTokensIter {
-    type Item = Token;
-    fn next(&mut self) -> Option<Self::Item> {
-        let next = self.packet.get(self.pos)?;
-        self.pos += 1;
-        match next {
-            ',' => self.next(),
-            '[' => Some(Token::Start),
-            ']' => Some(Token::End),
-            _ => {
-                let amt = next.to_digit(11)? as u8;
-                Some(Token::Value(amt))
-            }
-        }
+impl Display for Integer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 enum Packet {
     List(Vec<Packet>),
-    Value(u8),
+    Value(Integer),
 }
 
-fn create_packet(tokens: &mut Drain<Token>) -> Packet {
-    let mut packets: Vec<Packet> = Vec::new();
-    while let Some(next) = tokens.next() {
-        match next {
-            Token::Start => packets.push(create_packet(tokens)),
-            Token::Value(amt) => packets.push(Packet::Value(amt)),
-            Token::End => return Packet::List(packets),
-        };
+impl Packet {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        complete(packet, input.trim())
     }
+}
 
-    Packet::List(packets)
+// packet = integer | "[" [packet ("," packet)*] "]"
+fn packet(input: Input) -> ParseResult<Packet> {
+    if input.as_str().starts_with('[') {
+        list(input)
+    } else {
+        integer(input)
+    }
 }
 
-impl From<&str> for Packet {
-    fn from(s: &str) -> Self {
-        let p: PacketTokensIter = s.into();
-        let mut tokens = p.collect::<Vec<Token>>();
-        let mut tokens = tokens.drain(0..);
-        tokens.next();
-        create_packet(&mut tokens)
+fn list(input: Input) -> ParseResult<Packet> {
+    let (_, rest) = literal("[").parse(input)?;
+    if let Ok((_, rest)) = token(literal("]")).parse(rest) {
+        return Ok((Packet::List(Vec::new()), rest));
     }
+
+    let (items, rest) = separated(token(packet), token(literal(","))).parse(rest)?;
+    let (_, rest) = token(literal("]"))
+        .parse(rest)
+        .map_err(|_| rest.error("expected `,` or `]`"))?;
+    Ok((Packet::List(items), rest))
 }
 
-impl PartialOrd for Packet {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let ord = match (self, other) {
-            (Packet::List(left), Packet::List(right)) => {
-                let mut left = left.iter().peekable();
-                let mut right = right.iter().peekable();
-
-                let mut ord = Ordering::Equal;
-                while left.peek().is_some() || right.peek().is_some() {
-                    ord = match (left.next(), right.next()) {
-                        (Some(l), Some(r)) => l.partial_cmp(r).unwrap(),
-                        (Some(_), None) => Ordering::Greater,
-                        (None, Some(_)) => Ordering::Less,
-                        (None, None) => Ordering::Equal,
-                    };
-
-                    if ord != Ordering::Equal {
-                        break;
+fn integer(input: Input) -> ParseResult<Packet> {
+    let (digits, rest) = take_while(|c| c.is_ascii_digit()).parse(input)?;
+    if digits.is_empty() {
+        return Err(input.error("expected `[` or a number"));
+    }
+    Ok((Packet::Value(Integer::new(digits)), rest))
+}
+
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(Input::new(s))
+    }
+}
+
+impl Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Value(value) => write!(f, "{value}"),
+            Packet::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
                     }
+                    write!(f, "{item}")?;
                 }
-
-                ord
-            }
-            (Packet::Value(left), Packet::Value(right)) => left.cmp(right),
-            (Packet::List(_), Packet::Value(right)) => {
-                let list = vec![Packet::Value(*right)];
-                self.partial_cmp(&Packet::List(list)).unwrap()
+                write!(f, "]")
             }
-            (Packet::Value(left), Packet::List(_)) => {
-                let list = vec![Packet::Value(*left)];
-                Packet::List(list).partial_cmp(other).unwrap()
-            }
-        };
-
-        Some(ord)
+        }
     }
 }
 
 impl Ord for Packet {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        // a lone value compares as a list holding just that value, which a
+        // one-element slice stands in for without allocating
+        match (self, other) {
+            (Packet::Value(left), Packet::Value(right)) => left.cmp(right),
+            (Packet::List(left), Packet::List(right)) => left.iter().cmp(right),
+            (Packet::List(left), Packet::Value(_)) => left.iter().cmp(slice::from_ref(other)),
+            (Packet::Value(_), Packet::List(right)) => slice::from_ref(self).iter().cmp(right),
+        }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -123,11 +138,12 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input
+        self.input = Input::new(input)
             .lines()
-            .filter(|l| !l.is_empty())
-            .map(|l| l.into())
-            .collect();
+            .filter(|l| !l.trim().is_empty())
+            .map(Packet::parse)
+            .collect::<Result<_, _>>()
+            .unwrap();
     }
 
     fn solve_part1(&self) -> String {
@@ -135,7 +151,7 @@ impl Solver for Solution {
             .chunks(2)
             .enumerate()
             .fold(0, |acc, (i, pair)| {
-                let left = pair.get(0).unwrap();
+                let left = pair.first().unwrap();
                 let right = pair.get(1).unwrap();
                 if left < right {
                     acc + i + 1
@@ -148,8 +164,8 @@ impl Solver for Solution {
 
     fn solve_part2(&self) -> String {
         let mut packets = self.input.clone();
-        let divider1 = Packet::List(vec![Packet::List(vec![Packet::Value(2)])]);
-        let divider2 = Packet::List(vec![Packet::List(vec![Packet::Value(6)])]);
+        let divider1 = "[[2]]".parse::<Packet>().unwrap();
+        let divider2 = "[[6]]".parse::<Packet>().unwrap();
         packets.push(divider1.clone());
         packets.push(divider2.clone());
         packets.sort();
@@ -225,6 +241,35 @@ mod test {
         let solution = solver.solve_part2();
         assert_eq!(solution, "19570");
     }
+
+    #[test]
+    fn test_display_round_trips() {
+        for line in get_input().lines().filter(|l| !l.is_empty()) {
+            assert_eq!(line.parse::<Packet>().unwrap().to_string(), line);
+        }
+        let spaced = " [ 1 , [ ] ,[2, 007 ] ] ".parse::<Packet>().unwrap();
+        assert_eq!(spaced.to_string(), "[1,[],[2,7]]");
+    }
+
+    #[test]
+    fn test_large_values() {
+        let parse = |s: &str| s.parse::<Packet>().unwrap();
+        assert!(parse("[10]") > parse("[9]"));
+        assert!(parse("[[11],0]") < parse("[12]"));
+        assert!(parse("[123456789012345678901234567890]") > parse("[99999999999999999999]"));
+        assert_eq!(
+            parse("[18446744073709551616]").to_string(),
+            "[18446744073709551616]"
+        );
+    }
+
+    #[test]
+    fn test_malformed_packets() {
+        for (packet, column) in [("[1,2", 5), ("[1,,2]", 4), ("[1]]", 4), ("[a]", 2), ("", 1)] {
+            let error = packet.parse::<Packet>().unwrap_err();
+            assert_eq!(error.column, column, "{packet}: {error}");
+        }
+    }
 }
 
 const INPUT: &str = "\
@@ -1,167 +1,239 @@
-use std::{
-    collections::HashSet,
-    i16::MAX,
-    ops::{Add, AddAssign},
-    str::FromStr,
-    // thread,
-    // time::Duration,
-};
+use std::{fmt::Display, str::FromStr};
 
-use crate::solver::Solver;
+use crate::{
+    params::{assign, ParamError},
+    parse::{complete, literal, pair, preceded, separated, unsigned, Input, ParseError, Parser},
+    solver::Solver,
+};
 
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy)]
 struct Point(i16, i16);
 
-impl FromStr for Point {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s.split_once(',').unwrap();
-        Ok(Self(x.parse().unwrap(), y.parse().unwrap()))
-    }
+fn point<'a>() -> impl Parser<'a, Point> {
+    pair(unsigned(), preceded(literal(","), unsigned())).map(|(x, y)| Point(x, y))
 }
 
-impl Add for Point {
-    type Output = Self;
-    fn add(self, rhs: Self) -> Self::Output {
-        Point(self.0 + rhs.0, self.1 + rhs.1)
+impl Point {
+    fn parse_path(input: Input) -> Result<Vec<Self>, ParseError> {
+        complete(separated(point(), literal(" -> ")), input)
     }
 }
 
-impl AddAssign for Point {
-    fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
-        self.1 += rhs.1;
+impl FromStr for Point {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        complete(point(), Input::new(s))
     }
 }
 
-#[derive(Debug)]
-struct Line(Vec<Point>);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Air,
+    Rock,
+    Sand,
+}
 
-#[derive(Default, Debug)]
-pub struct Solution {
-    rock: HashSet<Point>,
+/// A slice of the cave wide enough for any pile of sand that can form in
+/// it, stored row by row.
+#[derive(Debug, Clone)]
+struct Cave {
+    tiles: Vec<Tile>,
     min_x: i16,
-    min_y: i16,
-    max_x: i16,
-    max_y: i16,
+    width: usize,
+    // everything past the last row is either the floor or the abyss
+    rows: usize,
+    floor: bool,
+    source: Point,
 }
 
-impl Solver for Solution {
-    fn get_input(&self) -> &'static str {
-        INPUT
-    }
+impl Cave {
+    /// Fills in the rock `paths`, with either a floor `floor_gap` below the
+    /// lowest rock, or nothing at all under it. Fails if the sand could
+    /// spread past what `i16` coordinates can hold.
+    fn new(paths: &[Vec<Point>], source: Point, floor_gap: Option<i16>) -> Result<Self, String> {
+        let points = paths.iter().flatten().chain([&source]);
+        let (min_x, max_x, max_y) = points.fold((i16::MAX, i16::MIN, i16::MIN), |(a, b, c), p| {
+            (a.min(p.0), b.max(p.0), c.max(p.1))
+        });
+        // sand spreads out at most one column per row it falls, and can
+        // step one past the rock on its way to the abyss
+        let too_big = || "the cave is too big for i16 coordinates".to_string();
+        let rows = max_y
+            .checked_add(floor_gap.unwrap_or(1))
+            .ok_or_else(too_big)?;
+        let min_x = min_x
+            .min(source.0.checked_sub(rows).ok_or_else(too_big)?)
+            .checked_sub(1)
+            .ok_or_else(too_big)?;
+        let max_x = max_x
+            .max(source.0.checked_add(rows).ok_or_else(too_big)?)
+            .checked_add(1)
+            .ok_or_else(too_big)?;
+        let width = (max_x as i32 - min_x as i32 + 1) as usize;
 
-    fn with_input(&mut self, input: &str) {
-        self.min_x = MAX;
-        self.min_y = MAX;
-        input.lines().for_each(|l| {
-            let lines: Vec<Point> = l
-                .split(" -> ")
-                .map(|c| c.parse().unwrap())
-                .inspect(|Point(x, y)| {
-                    self.min_x = self.min_x.min(*x);
-                    self.min_y = self.min_y.min(*y);
-                    self.max_y = self.max_y.max(*y);
-                    self.max_x = self.max_x.max(*x);
-                })
-                .collect();
-            lines.windows(2).for_each(|p| {
-                let Point(x1, y1) = p[0];
-                let Point(x2, y2) = p[1];
-
-                if x1 == x2 {
+        let mut cave = Self {
+            tiles: vec![Tile::Air; width * rows as usize],
+            min_x,
+            width,
+            rows: rows as usize,
+            floor: floor_gap.is_some(),
+            source,
+        };
+        for path in paths {
+            for line in path.windows(2) {
+                let (Point(x1, y1), Point(x2, y2)) = (line[0], line[1]);
+                for x in x1.min(x2)..=x1.max(x2) {
                     for y in y1.min(y2)..=y1.max(y2) {
-                        self.rock.insert(Point(x1, y));
-                    }
-                } else if y1 == y2 {
-                    for x in x1.min(x2)..=x1.max(x2) {
-                        self.rock.insert(Point(x, y1));
+                        let i = cave.index(Point(x, y)).unwrap();
+                        cave.tiles[i] = Tile::Rock;
                     }
-                } else {
-                    panic!("WARN: some lines are not purely vertical or horizontal")
                 }
-            })
-        });
+            }
+        }
+        Ok(cave)
     }
 
-    fn solve_part1(&self) -> String {
-        let mut in_the_abyss = false;
-        let mut sand_count = 0;
-
-        let mut sands: HashSet<Point> = HashSet::new();
-        let dirs = [Point(0, 1), Point(-1, 1), Point(1, 1)];
-        while !in_the_abyss {
-            let mut sand = Point(500, 0);
-            let mut is_done = false;
-            while !is_done {
-                is_done = true;
-                for dir in dirs {
-                    let target = sand + dir;
-                    if !self.rock.contains(&target) && !sands.contains(&target) {
-                        sand = target;
-                        is_done = false;
-                        break;
-                    }
-                }
-                if is_done {
-                    sand_count += 1;
-                    sands.insert(sand);
-                } else if sand.1 > self.max_y {
-                    in_the_abyss = true;
+    fn index(&self, Point(x, y): Point) -> Option<usize> {
+        let col = (x as i32 - self.min_x as i32) as usize;
+        (y >= 0 && (y as usize) < self.rows && col < self.width)
+            .then(|| y as usize * self.width + col)
+    }
+
+    /// Pours sand in until it either runs into the abyss or blocks the
+    /// source, returning how many grains came to rest.
+    fn fill(&mut self) -> usize {
+        // the path the current grain took to get where it is; the next grain
+        // follows the same path, so it can pick up where this one stopped
+        let mut path = vec![self.source];
+        let mut settled = 0;
+        while let Some(&grain) = path.last() {
+            if grain.1 as usize + 1 == self.rows {
+                if !self.floor {
                     break;
                 }
+            } else if let Some(next) = [0, -1, 1]
+                .map(|dx| Point(grain.0 + dx, grain.1 + 1))
+                .into_iter()
+                .find(|p| self.tiles[self.index(*p).unwrap()] == Tile::Air)
+            {
+                path.push(next);
+                continue;
             }
-        }
 
-        sand_count.to_string()
+            let i = self.index(grain).unwrap();
+            self.tiles[i] = Tile::Sand;
+            settled += 1;
+            path.pop();
+        }
+        settled
     }
+}
 
-    fn solve_part2(&self) -> String {
-        let mut standing_on_top = false;
-        let mut sand_count = 0;
-        // self.clear_terminal();
-        // self.draw_rocks();
-        let mut sands: HashSet<Point> = HashSet::new();
-        let dirs = [Point(0, 1), Point(-1, 1), Point(1, 1)];
-        while !standing_on_top {
-            let mut sand = Point(500, 0);
-            let mut is_done = false;
-            while !is_done {
-                is_done = true;
-                if sand.1 != self.max_y + 1 {
-                    for dir in dirs {
-                        let target = sand + dir;
-                        if !self.rock.contains(&target) && !sands.contains(&target) {
-                            // self.animate(&target, &sand);
-                            sand = target;
-                            is_done = false;
-                            break;
-                        }
-                    }
-                }
-                if is_done {
-                    sand_count += 1;
-                    sands.insert(sand);
-                    if sand.1 == 0 {
-                        standing_on_top = true;
-                        break;
-                    }
-                }
+/// Draws the part of the cave with anything in it, like the puzzle does.
+impl Display for Cave {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let used =
+            |col: usize| (0..self.rows).any(|row| self.tiles[row * self.width + col] != Tile::Air);
+        let source_col = (self.source.0 - self.min_x) as usize;
+        let first = (0..self.width)
+            .find(|c| used(*c))
+            .unwrap_or(source_col)
+            .min(source_col);
+        let last = (0..self.width)
+            .rfind(|c| used(*c))
+            .unwrap_or(source_col)
+            .max(source_col);
+        let last_row = (0..self.rows)
+            .rfind(|row| (first..=last).any(|c| self.tiles[row * self.width + c] != Tile::Air))
+            .unwrap_or(0);
+
+        for row in 0..=last_row {
+            for col in first..=last {
+                let tile = match self.tiles[row * self.width + col] {
+                    _ if (col, row) == (source_col, self.source.1 as usize) => '+',
+                    Tile::Air => '.',
+                    Tile::Rock => '#',
+                    Tile::Sand => 'o',
+                };
+                write!(f, "{tile}")?;
             }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
 
-            // if sand_count % 100 == 0 {
-            //     thread::sleep(Duration::from_millis(50));
-            //     self.clear_terminal();
-            //     self.draw_sand(&sands);
-            //     self.draw_rocks();
-            // }
+pub struct Solution {
+    paths: Vec<Vec<Point>>,
+    source: Point,
+    floor_gap: i16,
+    // print the cave once the sand has settled
+    render: bool,
+}
+
+impl Default for Solution {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            source: Point(500, 0),
+            floor_gap: 2,
+            render: false,
         }
+    }
+}
 
-        // self.clear_terminal();
-        // self.draw_sand(&sands);
-        // self.draw_rocks();
+impl Solution {
+    fn pour(&self, floor_gap: Option<i16>) -> String {
+        let mut cave = match Cave::new(&self.paths, self.source, floor_gap) {
+            Ok(cave) => cave,
+            Err(e) => return e,
+        };
+        let settled = cave.fill();
+        if self.render {
+            println!("{cave}");
+        }
+        settled.to_string()
+    }
+}
 
-        sand_count.to_string()
+impl Solver for Solution {
+    fn get_input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn with_input(&mut self, input: &str) {
+        self.paths = Input::new(input)
+            .lines()
+            .map(Point::parse_path)
+            .collect::<Result<_, _>>()
+            .unwrap();
+    }
+
+    fn solve_part1(&self) -> String {
+        self.pour(None)
+    }
+
+    fn solve_part2(&self) -> String {
+        self.pour(Some(self.floor_gap))
+    }
+
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "source" => assign(&mut self.source, name, value),
+            "floor_gap" => {
+                assign(&mut self.floor_gap, name, value)?;
+                if self.floor_gap < 1 {
+                    return Err(ParamError::Invalid {
+                        name: name.to_string(),
+                        message: "the floor must be below the lowest rock".to_string(),
+                    });
+                }
+                Ok(())
+            }
+            "render" => assign(&mut self.render, name, value),
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
@@ -191,6 +263,47 @@ mod test {
         assert_eq!(solution, "93");
     }
 
+    #[test]
+    fn test_render() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let mut cave = Cave::new(&solver.paths, solver.source, None).unwrap();
+        assert_eq!(cave.fill(), 24);
+        assert_eq!(
+            cave.to_string(),
+            "\
+......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########.
+"
+        );
+    }
+
+    #[test]
+    fn test_params() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        solver.set_param("source", "497,0").unwrap();
+        solver.set_param("floor_gap", "1").unwrap();
+        // the floor is now right under the lowest rock
+        assert_eq!(solver.solve_part2(), "71");
+        assert!(solver.set_param("source", "497").is_err());
+        assert!(solver.set_param("floor_gap", "0").is_err());
+
+        solver.set_param("floor_gap", "32767").unwrap();
+        assert_eq!(
+            solver.solve_part2(),
+            "the cave is too big for i16 coordinates"
+        );
+    }
+
     #[test]
     fn test_solution_part1() {
         let mut solver = Solution::default();
@@ -208,41 +321,6 @@ mod test {
     }
 }
 
-// const X_OFFSET: i16 = -300;
-// const Y_OFFSET: i16 = 2;
-// impl Solution {
-//     fn clear_terminal(&self) {
-//         print!("{esc}c", esc = 27 as char);
-//         print!("{:\n>250}", "");
-//     }
-
-//     fn draw_sand(&self, sand: &HashSet<Point>) {
-//         sand.iter().for_each(|Point(x, y)| {
-//             print!("\x1b[{};{}H", *y + Y_OFFSET, *x + X_OFFSET);
-//             print!("o");
-//         });
-//     }
-//     fn draw_rocks(&self) {
-//         self.rock.iter().for_each(|Point(x, y)| {
-//             print!("\x1b[{};{}H", *y + Y_OFFSET, *x + X_OFFSET);
-//             print!("x");
-//         });
-
-//         for x in 0..500 {
-//             print!("\x1b[{};{}H", self.max_y + Y_OFFSET + 2, x);
-//             print!("x");
-//         }
-//     }
-
-//     fn animate(&self, sand: &Point, prev: &Point) {
-//         thread::sleep(Duration::from_millis(16));
-//         print!("\x1b[{};{}H", sand.1 + Y_OFFSET, sand.0 + X_OFFSET);
-//         print!("o");
-//         print!("\x1b[{};{}H", prev.1 + Y_OFFSET, prev.0 + X_OFFSET);
-//         print!(" ");
-//     }
-// }
-
 const INPUT: &str = "\
 521,171 -> 525,171
 497,147 -> 497,137 -> 497,147 -> 499,147 -> 499,146 -> 499,147 -> 501,147 -> 501,142 -> 501,147 -> 503,147 -> 503,144 -> 503,147 -> 505,147 -> 505,140 -> 505,147
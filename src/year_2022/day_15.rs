@@ -1,6 +1,11 @@
-use std::{collections::HashSet, str::FromStr, sync::mpsc, thread::available_parallelism};
+use std::collections::HashSet;
 
-use crate::{solver::Solver, utils::ThreadPool};
+use crate::{
+    interval::{Interval, RangeSet},
+    params::{assign, ParamError},
+    parse::{complete, pattern, Input, ParseError},
+    solver::Solver,
+};
 
 #[derive(Debug, Hash, Clone)]
 struct Position(i64, i64);
@@ -15,26 +20,28 @@ struct Sensor {
     pos: Position,
     min_x: i64,
     max_x: i64,
-    min_y: i64,
-    max_y: i64,
     beacon: Beacon,
 }
 
-impl FromStr for Sensor {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (sensor, beacon) = s
-            .trim_start_matches("Sensor at x=")
-            .split_once(": closest beacon is at x=")
-            .unwrap();
-        let (sx, sy) = sensor.split_once(", y=").unwrap();
-        let (bx, by) = beacon.split_once(", y=").unwrap();
-        let bx: i64 = bx.parse().unwrap();
-        let by: i64 = by.parse().unwrap();
-        let sx: i64 = sx.parse().unwrap();
-        let sy: i64 = sy.parse().unwrap();
-
-        let dist = (sx - bx).abs() + (sy - by).abs();
+impl Sensor {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let [sx, sy, bx, by] = complete(
+            pattern("Sensor at x={}, y={}: closest beacon is at x={}, y={}"),
+            input,
+        )?;
+        let bx: i64 = bx.parse()?;
+        let by: i64 = by.parse()?;
+        let sx: i64 = sx.parse()?;
+        let sy: i64 = sy.parse()?;
+
+        // keeping max_x below i64::MAX lets coverage() include it
+        let distance = |a: i64, b: i64| a.checked_sub(b)?.checked_abs();
+        let (min_x, max_x) = distance(sx, bx)
+            .zip(distance(sy, by))
+            .and_then(|(dx, dy)| dx.checked_add(dy))
+            .and_then(|dist| Some((sx.checked_sub(dist)?, sx.checked_add(dist)?)))
+            .filter(|(_, max_x)| *max_x < i64::MAX)
+            .ok_or_else(|| input.error("the sensor's reach doesn't fit in i64"))?;
 
         let beacon = Beacon {
             pos: Position(bx, by),
@@ -42,51 +49,50 @@ impl FromStr for Sensor {
 
         let sensor = Sensor {
             pos: Position(sx, sy),
-            min_x: sx - dist,
-            max_x: sx + dist,
-            min_y: sy - dist,
-            max_y: sy + dist,
+            min_x,
+            max_x,
             beacon,
         };
         Ok(sensor)
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Solution {
     sensors: Vec<Sensor>,
-    part1_row: i64,
-    part2_max: usize,
+    // the row to count in part 1, and the far corner of the part 2 search
+    row: i64,
+    max: i64,
 }
 
-impl Solution {
-    fn get_ranges(&self, y: i64) -> Vec<(i64, i64)> {
-        get_ranges_between(&self.sensors, y, i64::MIN, i64::MAX)
+impl Default for Solution {
+    fn default() -> Self {
+        Self {
+            sensors: Vec::new(),
+            row: 2000000,
+            max: 4000000,
+        }
     }
 }
 
-fn get_ranges_between(sensors: &Vec<Sensor>, y: i64, min_x: i64, max_x: i64) -> Vec<(i64, i64)> {
-    let mut ranges = sensors
-        .iter()
-        .filter(|s| (s.min_y..=s.max_y).contains(&y))
-        .map(|s| {
-            let offset = (y - s.pos.1).abs();
-            (min_x.max(s.min_x + offset), max_x.min(s.max_x - offset))
-        })
-        .collect::<Vec<(i64, i64)>>();
-
-    ranges.sort_by_key(|r| r.0);
-    let first = *ranges.first().unwrap();
-    let merged_ranges = ranges.iter().skip(1).fold(vec![first], |mut acc, r| {
-        let last = acc.last_mut().unwrap();
-        if r.0 <= last.1 {
-            last.1 = last.1.max(r.1);
-        } else {
-            acc.push(*r);
-        }
-        acc
-    });
-    merged_ranges
+impl Sensor {
+    // the slice of row `y` this sensor can see, empty if it can't reach
+    fn coverage(&self, y: i64) -> Interval {
+        let offset = (y - self.pos.1).abs();
+        Interval::inclusive(self.min_x + offset, self.max_x - offset).unwrap()
+    }
+
+    fn radius(&self) -> i64 {
+        self.pos.0 - self.min_x
+    }
+
+    fn reaches(&self, Position(x, y): &Position) -> bool {
+        (x - self.pos.0).abs() + (y - self.pos.1).abs() <= self.radius()
+    }
+}
+
+fn get_coverage(sensors: &[Sensor], y: i64) -> RangeSet {
+    sensors.iter().map(|s| s.coverage(y)).collect()
 }
 
 impl Solver for Solution {
@@ -95,75 +101,86 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.part1_row = input.lines().next().unwrap().parse().unwrap();
-        self.part2_max = self.part1_row as usize * 2;
-        self.sensors = input.lines().skip(1).map(|l| l.parse().unwrap()).collect();
+        self.sensors = Input::new(input)
+            .lines()
+            .map(Sensor::parse)
+            .collect::<Result<_, _>>()
+            .unwrap();
     }
 
     fn solve_part1(&self) -> String {
-        let y = self.part1_row;
-        let merged_ranges = self.get_ranges(y);
+        let y = self.row;
+        let coverage = get_coverage(&self.sensors, y);
 
         let beacons = self
             .sensors
             .iter()
-            .filter(|s| {
-                s.beacon.pos.1 == y
-                    && merged_ranges
-                        .iter()
-                        .filter(|(r1, r2)| (r1..=r2).contains(&&s.beacon.pos.0))
-                        .count()
-                        > 0
-            })
+            .filter(|s| s.beacon.pos.1 == y && coverage.contains(s.beacon.pos.0))
             .map(|s| s.beacon.pos.0)
             .collect::<HashSet<i64>>()
             .len();
 
-        let count = merged_ranges
-            .iter()
-            .fold(0, |acc, (r1, r2)| acc + 1 + r2 - r1);
-
-        (count - beacons as i64).to_string()
+        (coverage.len() - beacons as u64).to_string()
     }
 
     fn solve_part2(&self) -> String {
-        let max_workers = available_parallelism().unwrap().get();
-
-        println!("using {max_workers} worker threads");
-
-        let (res_tx, res_rx) = mpsc::channel::<String>();
-
-        let pool = ThreadPool::new(max_workers);
-
-        let res_tx = res_tx.clone();
-
-        let max_range = self.part2_max as i64;
-
-        for i in 0..max_workers {
-            let size = self.part2_max / max_workers;
-            let sensors = self.sensors.clone();
-            let res_tx = res_tx.clone();
-            pool.execute(move || {
-                for y in i * size..(i + 1) * size {
-                    let ranges = get_ranges_between(&sensors, y as i64, 0, max_range);
-                    if ranges.len() == 2 {
-                        res_tx
-                            .send((((ranges[0].1 + 1) * 4000000) + y as i64).to_string())
-                            .unwrap();
-                        drop(res_tx);
-                        break;
-                    }
-                }
-            });
+        // a lone uncovered point is boxed in by the sensors around it, so it
+        // lies just outside their diamonds, where two boundary lines cross
+        let max = self.max;
+        let (mut rising, mut falling) = (Vec::new(), Vec::new());
+        for s in &self.sensors {
+            let (x, y, r) = (s.pos.0, s.pos.1, s.radius() + 1);
+            rising.extend([x - y - r, x - y + r]); // lines of x - y
+            falling.extend([x + y - r, x + y + r]); // lines of x + y
         }
+        let crossings = rising
+            .iter()
+            .flat_map(|a| falling.iter().map(move |b| (*a, *b)))
+            .filter(|(a, b)| (a + b) % 2 == 0)
+            .map(|(a, b)| Position((a + b) / 2, (b - a) / 2));
 
-        drop(pool);
-        drop(res_tx);
+        // against the edge of the search area it only needs boxing in on
+        // the other sides, so also try where each line meets the edges
+        let edges = rising
+            .iter()
+            .flat_map(|a| {
+                [
+                    Position(*a, 0),
+                    Position(a + max, max),
+                    Position(0, -a),
+                    Position(max, max - a),
+                ]
+            })
+            .chain(falling.iter().flat_map(|b| {
+                [
+                    Position(*b, 0),
+                    Position(b - max, max),
+                    Position(0, *b),
+                    Position(max, b - max),
+                ]
+            }));
+        let corners = [
+            Position(0, 0),
+            Position(0, max),
+            Position(max, 0),
+            Position(max, max),
+        ];
+
+        crossings
+            .chain(edges)
+            .chain(corners)
+            .filter(|p| (0..=max).contains(&p.0) && (0..=max).contains(&p.1))
+            .find(|p| !self.sensors.iter().any(|s| s.reaches(p)))
+            .map_or("failed to find!".to_string(), |p| {
+                (p.0 * 4000000 + p.1).to_string()
+            })
+    }
 
-        if let Ok(r) = res_rx.recv() {
-            r
-        } else {
-            "failed to find!".to_string()
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "row" => assign(&mut self.row, name, value),
+            "max" => assign(&mut self.max, name, value),
+            _ => Err(ParamError::unknown(name)),
         }
     }
 }
@@ -174,7 +191,6 @@ mod test {
 
     fn get_example_input<'a>() -> &'a str {
         "\
-10
 Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16
 Sensor at x=13, y=2: closest beacon is at x=15, y=3
@@ -194,6 +210,7 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3"
     #[test]
     fn test_solution_example1() {
         let mut solver = Solution::default();
+        solver.set_param("row", "10").unwrap();
         solver.with_input(get_example_input());
         let solution = solver.solve_part1();
         assert_eq!(solution, "26");
@@ -202,11 +219,24 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3"
     #[test]
     fn test_solution_example2() {
         let mut solver = Solution::default();
+        solver.set_param("max", "20").unwrap();
         solver.with_input(get_example_input());
         let solution = solver.solve_part2();
         assert_eq!(solution, "56000011");
     }
 
+    #[test]
+    fn test_far_sensor() {
+        let far = format!(
+            "Sensor at x={}, y=0: closest beacon is at x=0, y=0",
+            i64::MAX
+        );
+        let error = Sensor::parse(Input::new(&far)).unwrap_err();
+        assert_eq!(error.message, "the sensor's reach doesn't fit in i64");
+        let near = far.replace(&i64::MAX.to_string(), &(i64::MAX / 2).to_string());
+        assert!(Sensor::parse(Input::new(&near)).is_ok());
+    }
+
     #[test]
     fn test_solution_part1() {
         let mut solver = Solution::default();
@@ -225,7 +255,6 @@ Sensor at x=20, y=1: closest beacon is at x=15, y=3"
 }
 
 const INPUT: &str = "\
-2000000
 Sensor at x=1384790, y=3850432: closest beacon is at x=2674241, y=4192888
 Sensor at x=2825953, y=288046: closest beacon is at x=2154954, y=-342775
 Sensor at x=3553843, y=2822363: closest beacon is at x=3444765, y=2347460
@@ -1,125 +1,474 @@
 use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
+    cmp::{Ordering, Reverse},
+    collections::HashMap,
+    fmt::Display,
 };
 
-use crate::solver::Solver;
+use crate::{
+    bitset::BitSet,
+    params::{assign, ParamError},
+    parse::{
+        complete, literal, one_of, pair, preceded, separated, unsigned, word, Input, ParseError,
+    },
+    search::bfs_distances,
+    solver::Solver,
+    utils::FxHashMap,
+};
 
 #[derive(Default, Debug, Clone)]
 struct Valve {
-    bit_mask: u32,
     key: String,
     flow_rate: usize,
-    neighbors: Vec<(usize, String)>,
+    neighbors: Vec<String>,
 }
 
-impl FromStr for Valve {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (key, rest) = s
-            .trim_start_matches("Valve ")
-            .split_once(" has flow rate=")
-            .unwrap();
-        let (flow_rate, neighbors) = rest.split_once(';').unwrap();
-        let neighbors = neighbors
-            .trim_start_matches(|c: char| c.is_ascii_lowercase() || c.is_ascii_whitespace());
+impl Valve {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let (key, (flow_rate, neighbors)) = complete(
+            pair(
+                preceded(literal("Valve "), word()),
+                pair(
+                    preceded(literal(" has flow rate="), unsigned()),
+                    preceded(
+                        one_of(&["; tunnels lead to valves ", "; tunnel leads to valve "]),
+                        separated(word(), literal(", ")),
+                    ),
+                ),
+            ),
+            input,
+        )?;
         Ok(Self {
-            bit_mask: 0,
             key: key.to_string(),
-            flow_rate: flow_rate.parse().unwrap(),
-            neighbors: neighbors.split(", ").map(|l| (1, l.to_string())).collect(),
+            flow_rate,
+            neighbors: neighbors.iter().map(|l| l.to_string()).collect(),
         })
     }
 }
 
-struct ValveNetworkBFSIter<'a> {
-    network: &'a ValveNetwork,
-    visited: HashSet<String>,
-    to_visit: Vec<(usize, String)>,
+/// One valve being opened, at the end of `minute`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Opening {
+    agent: usize,
+    valve: String,
+    minute: usize,
 }
 
-impl<'a> ValveNetworkBFSIter<'a> {
-    fn new(network: &'a ValveNetwork, start: String) -> Self {
-        Self {
-            network,
-            visited: HashSet::new(),
-            to_visit: vec![(1, start)],
+#[derive(Debug, Default, Clone)]
+struct Schedule {
+    pressure: usize,
+    // in the order they happen
+    openings: Vec<Opening>,
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for o in &self.openings {
+            writeln!(
+                f,
+                "minute {}: agent {} opens {}",
+                o.minute, o.agent, o.valve
+            )?;
         }
+        write!(f, "{} pressure released", self.pressure)
     }
 }
 
-impl<'a> Iterator for ValveNetworkBFSIter<'a> {
-    type Item = (usize, &'a Valve);
+/// Most useful valves [`ValveNetwork::share`] builds tables over every set
+/// of; past that it searches pairings of the sets one agent can open.
+const MAX_TABLE_VALVES: usize = 20;
+
+/// Just the valves worth opening, with the time it takes to walk from any
+/// of them (or the start) to each one and open it. Sets of valves are
+/// [`BitSet`]s of their indices.
+#[derive(Default, Debug, Clone)]
+struct ValveNetwork {
+    keys: Vec<String>,
+    flow_rates: Vec<usize>,
+    // `costs[from][to]`, where `from` can also be `start`
+    costs: Vec<Vec<usize>>,
+    start: usize,
+}
+
+impl ValveNetwork {
+    fn new(valves: &[Valve]) -> Self {
+        let by_key = valves
+            .iter()
+            .map(|v| (v.key.as_str(), v))
+            .collect::<HashMap<&str, &Valve>>();
+        let useful = valves
+            .iter()
+            .filter(|v| v.flow_rate > 0)
+            .collect::<Vec<&Valve>>();
+
+        let costs = useful
+            .iter()
+            .map(|v| v.key.as_str())
+            .chain(["AA"])
+            .map(|from| {
+                let distances = bfs_distances([from], |key| {
+                    by_key
+                        .get(key)
+                        .map_or(&[][..], |v| &v.neighbors)
+                        .iter()
+                        .map(String::as_str)
+                });
+                // one extra minute to open the valve once there
+                useful
+                    .iter()
+                    .map(|to| distances.get(to.key.as_str()).map_or(usize::MAX, |d| d + 1))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            keys: useful.iter().map(|v| v.key.clone()).collect(),
+            flow_rates: useful.iter().map(|v| v.flow_rate).collect(),
+            costs,
+            start: useful.len(),
+        }
+    }
+
+    /// The most pressure a single agent can release by opening exactly each
+    /// set of valves it can open in time.
+    fn best_per_set(&self, minutes: usize) -> FxHashMap<BitSet, usize> {
+        let mut best = FxHashMap::default();
+        self.explore(&mut best, self.start, minutes, &mut BitSet::new(), 0);
+        best
+    }
+
+    fn explore(
+        &self,
+        best: &mut FxHashMap<BitSet, usize>,
+        at: usize,
+        time_left: usize,
+        opened: &mut BitSet,
+        pressure: usize,
+    ) {
+        match best.get_mut(opened) {
+            Some(entry) => *entry = pressure.max(*entry),
+            None => {
+                best.insert(opened.clone(), pressure);
+            }
+        }
+        for next in 0..self.keys.len() {
+            let cost = self.costs[at][next];
+            if cost < time_left && !opened.contains(next) {
+                let time_left = time_left - cost;
+                let pressure = pressure + self.flow_rates[next] * time_left;
+                opened.insert(next);
+                self.explore(best, next, time_left, opened, pressure);
+                opened.remove(next);
+            }
+        }
+    }
+
+    /// An order to open exactly the valves in `set` that releases `pressure`,
+    /// as `(valve, minute)` pairs.
+    fn route(&self, minutes: usize, set: &BitSet, pressure: usize) -> Option<Vec<(usize, usize)>> {
+        let mut route = Vec::new();
+        self.find_route(&mut route, minutes, set, pressure)
+            .then_some(route)
+    }
+
+    fn find_route(
+        &self,
+        route: &mut Vec<(usize, usize)>,
+        minutes: usize,
+        set: &BitSet,
+        pressure: usize,
+    ) -> bool {
+        let (at, minute) = route.last().copied().unwrap_or((self.start, 0));
+        let opened = route.iter().map(|(v, _)| *v).collect::<BitSet>();
+        if opened == *set {
+            return pressure == 0;
+        }
+
+        for next in set.iter().filter(|v| !opened.contains(*v)) {
+            let minute = minute.saturating_add(self.costs[at][next]);
+            let released = self.flow_rates[next] * minutes.saturating_sub(minute);
+            if minute < minutes && released <= pressure {
+                route.push((next, minute));
+                if self.find_route(route, minutes, set, pressure - released) {
+                    return true;
+                }
+                route.pop();
+            }
+        }
+        false
+    }
+
+    /// The best way for `agents` agents working at once to release pressure
+    /// in `minutes`.
+    fn plan(&self, minutes: usize, agents: usize) -> Schedule {
+        let best = self.best_per_set(minutes);
+        let sets = match agents {
+            0 => Vec::new(),
+            1 => {
+                let (set, _) = best.iter().max_by_key(|(_, p)| **p).unwrap();
+                vec![set.clone()]
+            }
+            _ => self.share(&best, agents),
+        };
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.to_visit.is_empty() {
-            return None;
+        let mut schedule = Schedule::default();
+        for (agent, set) in sets.iter().enumerate() {
+            let pressure = best.get(set).copied().unwrap_or(0);
+            let route = self
+                .route(minutes, set, pressure)
+                .expect("every best set has a route");
+            schedule.pressure += pressure;
+            schedule
+                .openings
+                .extend(route.into_iter().map(|(v, minute)| Opening {
+                    agent,
+                    valve: self.keys[v].clone(),
+                    minute,
+                }));
         }
+        schedule.openings.sort_by_key(|o| (o.minute, o.agent));
+        schedule
+    }
 
-        let next = self.to_visit.drain(0..1).next()?;
+    /// Splits the valves between `agents` agents, giving the set each one
+    /// should open.
+    fn share(&self, best: &FxHashMap<BitSet, usize>, agents: usize) -> Vec<BitSet> {
+        let valves = self.keys.len();
+        if valves > MAX_TABLE_VALVES {
+            return share_by_search(best, agents);
+        }
 
-        let valve = self.network.0.get(&next.1).unwrap();
-        self.visited.insert(next.1);
+        // the tables are indexed by sets as masks over the valves
+        let full = (1 << valves) - 1;
+        let mut masks = vec![None; full + 1];
+        for (set, p) in best {
+            masks[set.iter().fold(0, |mask, v| mask | 1 << v)] = Some(*p);
+        }
 
-        #[allow(clippy::needless_collect)]
-        let to_visit = valve
-            .neighbors
+        // tables[a][set] is the most `a + 1` agents can release using only
+        // valves from `set`, along with the valves the first of them opens.
+        // For one agent that's the best subset, found by letting every set
+        // take the best of the sets one valve smaller.
+        let mut single = masks
             .iter()
-            .map(|v| (next.0 + v.0, v.1.clone()))
-            .filter(|v| {
-                let visited = !self.visited.contains(&*v.1);
-                let to_visited = !self.to_visit.contains(v);
-                visited && to_visited
-            })
-            .collect::<Vec<(usize, String)>>();
+            .enumerate()
+            .map(|(set, p)| p.map_or((0, 0), |p| (p, set)))
+            .collect::<Vec<(usize, usize)>>();
+        for bit in (0..valves).map(|v| 1 << v) {
+            for set in (0..=full).filter(|set| set & bit != 0) {
+                if single[set ^ bit].0 > single[set].0 {
+                    single[set] = single[set ^ bit];
+                }
+            }
+        }
 
-        self.to_visit.extend(to_visit.into_iter());
+        // each extra agent takes some subset and leaves the rest to the
+        // others; only the last table is needed for just the full set
+        let mut tables = vec![single];
+        for a in 1..agents {
+            let others = tables.last().unwrap();
+            let sets = if a + 1 == agents {
+                full..=full
+            } else {
+                0..=full
+            };
+            let mut table = vec![(0, 0); full + 1];
+            for set in sets {
+                let mut subset = set;
+                loop {
+                    if let Some(p) = masks[subset] {
+                        let total = p + others[set ^ subset].0;
+                        if total > table[set].0 {
+                            table[set] = (total, subset);
+                        }
+                    }
+                    if subset == 0 {
+                        break;
+                    }
+                    subset = (subset - 1) & set;
+                }
+            }
+            tables.push(table);
+        }
 
-        Some((next.0, valve))
+        let mut remaining = full;
+        let mut sets = Vec::new();
+        for table in tables.iter().rev() {
+            let set = table[remaining].1;
+            remaining ^= set;
+            sets.push((0..valves).filter(|v| set & 1 << v != 0).collect());
+        }
+        sets
     }
 }
-#[derive(Default, Debug, Clone)]
-struct ValveNetwork(HashMap<String, Valve>);
 
-impl ValveNetwork {
-    fn bfs_iter(&self, start: String) -> ValveNetworkBFSIter {
-        ValveNetworkBFSIter::new(self, start)
+/// Splits the valves between `agents` agents without a table over every set
+/// of valves: the first agents try sets in order of pressure, and the last
+/// takes the best set [`SetTrie`] finds among what's left.
+fn share_by_search(best: &FxHashMap<BitSet, usize>, agents: usize) -> Vec<BitSet> {
+    let trie = SetTrie::new(best);
+    let mut found = (0, Vec::new());
+    let mut picked = Vec::new();
+    trie.pick(0, agents, &mut picked, &BitSet::new(), 0, &mut found);
+
+    let mut shares = found
+        .1
+        .into_iter()
+        .map(|i| trie.sets[i].0.clone())
+        .collect::<Vec<BitSet>>();
+    shares.resize(agents, BitSet::new());
+    shares
+}
+
+/// The sets one agent can open, ordered so that the sets agreeing on which
+/// of the valves before `v` they hold are a contiguous run for every `v`,
+/// making a trie of them with the valves as levels.
+struct SetTrie<'a> {
+    sets: Vec<(&'a BitSet, usize)>,
+    // `max_pressure[k][i]` is the most pressure among `sets[i..i + 2^k]`
+    max_pressure: Vec<Vec<usize>>,
+    // indices into `sets`, most pressure first
+    by_pressure: Vec<usize>,
+}
+
+impl<'a> SetTrie<'a> {
+    fn new(best: &'a FxHashMap<BitSet, usize>) -> Self {
+        let mut sets = best
+            .iter()
+            .map(|(set, p)| (set, *p))
+            .collect::<Vec<(&BitSet, usize)>>();
+        // at the first valve two sets differ on, the one without it goes first
+        sets.sort_unstable_by(|(a, _), (b, _)| {
+            a.symmetric_difference(b)
+                .iter()
+                .next()
+                .map_or(Ordering::Equal, |v| a.contains(v).cmp(&b.contains(v)))
+        });
+
+        let mut max_pressure = vec![sets.iter().map(|(_, p)| *p).collect::<Vec<usize>>()];
+        for k in 1.. {
+            if 1 << k > sets.len() {
+                break;
+            }
+            let (prev, half) = (&max_pressure[k - 1], 1 << (k - 1));
+            let next = (0..=sets.len() - (1 << k))
+                .map(|i| prev[i].max(prev[i + half]))
+                .collect();
+            max_pressure.push(next);
+        }
+        let mut by_pressure = (0..sets.len()).collect::<Vec<usize>>();
+        by_pressure.sort_unstable_by_key(|i| Reverse(sets[*i].1));
+        Self {
+            sets,
+            max_pressure,
+            by_pressure,
+        }
+    }
+
+    fn max_pressure(&self, lo: usize, hi: usize) -> usize {
+        let k = (hi - lo).ilog2() as usize;
+        self.max_pressure[k][lo].max(self.max_pressure[k][hi - (1 << k)])
     }
 
-    fn find_optimal_flow(
+    /// Picks sets for `agents` more agents that don't share valves with
+    /// `used`, recording the best total in `found`. All but the last agent
+    /// try sets in `by_pressure` order, starting `from` there.
+    fn pick(
         &self,
-        dp: &mut HashMap<u32, usize>,
-        current_key: &str,
-        time_left: usize,
-        open_valves: u32, // bit mask of open valves
-        flowed: usize,
+        from: usize,
+        agents: usize,
+        picked: &mut Vec<usize>,
+        used: &BitSet,
+        pressure: usize,
+        found: &mut (usize, Vec<usize>),
     ) {
-        let current = dp.get(&open_valves).unwrap_or(&0).to_owned();
-        dp.insert(open_valves, flowed.max(current));
+        if pressure > found.0 {
+            *found = (pressure, picked.clone());
+        }
+        if agents == 1 {
+            let mut best = (found.0 - pressure, None);
+            self.best_avoiding(0, self.sets.len(), 0, used, &mut best);
+            if let (p, Some(i)) = best {
+                picked.push(i);
+                *found = (pressure + p, picked.clone());
+                picked.pop();
+            }
+            return;
+        }
 
-        self.0
-            .get(current_key)
-            .unwrap()
-            .neighbors
-            .iter()
-            .map(|(mins, key)| (mins, self.0.get(key).unwrap()))
-            .filter(|(mins, v)| *mins < &time_left && (v.bit_mask & open_valves) == 0)
-            .for_each(|(mins, v)| {
-                let time_left = time_left - mins;
-                self.find_optimal_flow(
-                    dp,
-                    &v.key,
-                    time_left,
-                    open_valves | v.bit_mask,
-                    flowed + (v.flow_rate * time_left),
-                );
-            });
+        for (rank, &i) in self.by_pressure.iter().enumerate().skip(from) {
+            let (set, p) = self.sets[i];
+            // the sets only get worse from here, so stop once even giving
+            // every agent left this one can't win
+            if pressure + p * agents <= found.0 {
+                break;
+            }
+            if set.is_disjoint(used) {
+                picked.push(i);
+                let used = used.union(set);
+                self.pick(rank + 1, agents - 1, picked, &used, pressure + p, found);
+                picked.pop();
+            }
+        }
+    }
+
+    /// Finds the set in `sets[lo..hi]`, all of which agree on the valves
+    /// before `valve`, that releases the most pressure above `best.0`
+    /// without using any valve in `used`.
+    fn best_avoiding(
+        &self,
+        lo: usize,
+        hi: usize,
+        valve: usize,
+        used: &BitSet,
+        best: &mut (usize, Option<usize>),
+    ) {
+        if lo == hi || self.max_pressure(lo, hi) <= best.0 {
+            return;
+        }
+        if hi - lo == 1 {
+            if self.sets[lo].0.is_disjoint(used) {
+                *best = (self.sets[lo].1, Some(lo));
+            }
+            return;
+        }
+        // the sets are all different, so some later valve splits them
+        let mid = lo + self.sets[lo..hi].partition_point(|(set, _)| !set.contains(valve));
+        if !used.contains(valve) {
+            self.best_avoiding(mid, hi, valve + 1, used, best);
+        }
+        self.best_avoiding(lo, mid, valve + 1, used, best);
     }
 }
 
-#[derive(Default, Debug)]
 pub struct Solution {
     input: ValveNetwork,
+    part1_minutes: usize,
+    part2_minutes: usize,
+    part2_agents: usize,
+    // print the schedule behind each answer
+    schedule: bool,
+}
+
+impl Default for Solution {
+    fn default() -> Self {
+        Self {
+            input: ValveNetwork::default(),
+            part1_minutes: 30,
+            part2_minutes: 26,
+            part2_agents: 2,
+            schedule: false,
+        }
+    }
+}
+
+impl Solution {
+    fn pressure(&self, minutes: usize, agents: usize) -> String {
+        let schedule = self.input.plan(minutes, agents);
+        if self.schedule {
+            println!("{schedule}");
+        }
+        schedule.pressure.to_string()
+    }
 }
 
 impl Solver for Solution {
@@ -128,67 +477,39 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        let network = ValveNetwork(
-            input
-                .lines()
-                .map(|l| {
-                    let valve = l.parse::<Valve>().unwrap();
-                    (valve.key.clone(), valve)
-                })
-                .collect::<HashMap<String, Valve>>(),
-        );
-
-        // compact network using breadth first search iterator
-        let compact_network = ValveNetwork(
-            network
-                .0
-                .iter()
-                .filter(|(_, v)| v.flow_rate > 0 || v.key == *"AA")
-                .enumerate()
-                .map(|(i, (key, v))| {
-                    (
-                        key.clone(),
-                        Valve {
-                            bit_mask: 2u32.pow(i as u32),
-                            key: key.clone(),
-                            flow_rate: v.flow_rate,
-                            // using network iterator we can build a complete
-                            // list of navigatable neighbors ordered by weight
-                            neighbors: network
-                                .bfs_iter(key.clone())
-                                .filter(|v| &v.1.key != key && v.1.flow_rate > 0)
-                                .map(|(mins, v)| (mins, v.key.clone()))
-                                .collect::<Vec<(usize, String)>>(),
-                        },
-                    )
-                })
-                .collect::<HashMap<String, Valve>>(),
-        );
-
-        self.input = compact_network;
+        let valves = Input::new(input)
+            .lines()
+            .map(Valve::parse)
+            .collect::<Result<Vec<Valve>, _>>()
+            .unwrap();
+        self.input = ValveNetwork::new(&valves);
     }
 
     fn solve_part1(&self) -> String {
-        let mut solutions = HashMap::new();
-        self.input.find_optimal_flow(&mut solutions, "AA", 30, 0, 0);
-
-        let max_flow = solutions.iter().max_by_key(|s| s.1).unwrap();
-
-        max_flow.1.to_string()
+        self.pressure(self.part1_minutes, 1)
     }
 
     fn solve_part2(&self) -> String {
-        let mut solutions = HashMap::new();
-        self.input.find_optimal_flow(&mut solutions, "AA", 26, 0, 0);
-        let mut max_flow = 0;
-        for (k1, v1) in solutions.iter() {
-            for (k2, v2) in solutions.iter() {
-                if (k1 & k2) == 0 {
-                    max_flow = max_flow.max(v1 + v2);
+        self.pressure(self.part2_minutes, self.part2_agents)
+    }
+
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "part1_minutes" => assign(&mut self.part1_minutes, name, value),
+            "part2_minutes" => assign(&mut self.part2_minutes, name, value),
+            "part2_agents" => {
+                assign(&mut self.part2_agents, name, value)?;
+                if self.part2_agents == 0 {
+                    return Err(ParamError::Invalid {
+                        name: name.to_string(),
+                        message: "at least one agent has to open valves".to_string(),
+                    });
                 }
+                Ok(())
             }
+            "schedule" => assign(&mut self.schedule, name, value),
+            _ => Err(ParamError::unknown(name)),
         }
-        max_flow.to_string()
     }
 }
 
@@ -226,6 +547,78 @@ Valve JJ has flow rate=21; tunnel leads to valve II"
         assert_eq!(solution, "1707");
     }
 
+    fn network() -> ValveNetwork {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        solver.input
+    }
+
+    #[test]
+    fn test_schedule() {
+        let schedule = network().plan(30, 1);
+        assert_eq!(
+            schedule.to_string(),
+            "\
+minute 2: agent 0 opens DD
+minute 5: agent 0 opens BB
+minute 9: agent 0 opens JJ
+minute 17: agent 0 opens HH
+minute 21: agent 0 opens EE
+minute 24: agent 0 opens CC
+1651 pressure released"
+        );
+
+        let schedule = network().plan(26, 2);
+        assert_eq!(schedule.pressure, 1707);
+        assert_eq!(schedule.openings.len(), 6);
+    }
+
+    #[test]
+    fn test_more_agents() {
+        let network = network();
+        assert_eq!(network.plan(26, 3).pressure, 1794);
+        assert_eq!(network.plan(26, 4).pressure, 1825);
+        assert_eq!(network.plan(0, 2).pressure, 0);
+        assert!(network.plan(26, 0).openings.is_empty());
+        assert!(Solution::default().set_param("part2_agents", "0").is_err());
+
+        // the search used past MAX_TABLE_VALVES agrees with the tables
+        let best = network.best_per_set(26);
+        for agents in 2..=4 {
+            let total = |sets: Vec<BitSet>| {
+                assert_eq!(sets.len(), agents);
+                sets.iter().map(|set| best[set]).sum::<usize>()
+            };
+            assert_eq!(
+                total(share_by_search(&best, agents)),
+                total(network.share(&best, agents))
+            );
+        }
+    }
+
+    #[test]
+    fn test_many_valves() {
+        // a corridor of 40 valves leading away from AA
+        let names = (0..40).map(|i| format!("V{i:02}")).collect::<Vec<String>>();
+        let mut lines = vec!["Valve AA has flow rate=0; tunnel leads to valve V00".to_string()];
+        for (i, name) in names.iter().enumerate() {
+            let prev = if i == 0 { "AA" } else { &names[i - 1] };
+            let next = names.get(i + 1).map_or("", |n| n.as_str());
+            let tunnels = [prev, next].join(", ");
+            let tunnels = tunnels.trim_end_matches(", ");
+            lines.push(format!(
+                "Valve {name} has flow rate=1; tunnels lead to valves {tunnels}"
+            ));
+        }
+
+        let mut solver = Solution::default();
+        solver.with_input(&lines.join("\n"));
+        solver.set_param("part1_minutes", "12").unwrap();
+        // opening V00 to V04 on the way down the corridor
+        assert_eq!(solver.solve_part1(), "30");
+        assert_eq!(solver.solve_part2(), "208");
+    }
+
     #[test]
     fn test_solution_part1() {
         let mut solver = Solution::default();
@@ -1,9 +1,35 @@
-use std::{collections::HashMap, hash::Hash, thread::sleep, time::Duration};
+use std::{fmt::Write, thread::sleep, time::Duration};
 
-use crate::{solver::Solver, utils::clear_terminal};
+use crate::{
+    cycle::CycleDetector,
+    params::{assign, ParamError},
+    parse::{Input, ParseError},
+    solver::Solver,
+    utils::clear_terminal,
+};
 
-const MIN_X: u8 = 0;
-const MAX_X: u8 = 7;
+/// The rocks in the order they fall, each drawn the way the puzzle does.
+const SHAPES: &str = "\
+####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+
+/// How deep the surface profile used for cycle detection looks.
+const SURFACE_DEPTH: usize = 64;
 
 #[derive(Debug)]
 enum Dir {
@@ -21,199 +47,217 @@ impl From<char> for Dir {
     }
 }
 
-#[derive(Debug)]
-enum Shape {
-    Flat,
-    Cross,
-    Angle,
-    Tall,
-    Block,
+/// A rock as one bit mask per row, bottom row first, with bit 0 for its
+/// leftmost column.
+#[derive(Debug, Clone)]
+struct Shape {
+    rows: Vec<u64>,
+    width: u32,
 }
 
 impl Shape {
-    fn get_pieces(&self, x: u8) -> [u8; 4] {
-        match self {
-            Shape::Flat => [0b1111000 >> x, 0, 0, 0],
-            Shape::Cross => [0b0100000 >> x, 0b1110000 >> x, 0b0100000 >> x, 0],
-            Shape::Angle => [0b1110000 >> x, 0b0010000 >> x, 0b0010000 >> x, 0],
-            Shape::Tall => [
-                0b1000000 >> x,
-                0b1000000 >> x,
-                0b1000000 >> x,
-                0b1000000 >> x,
-            ],
-            Shape::Block => [0b1100000 >> x, 0b1100000 >> x, 0, 0],
+    /// Reads a rock drawn with `#`s, ignoring any empty rows above or below
+    /// it and empty columns to its left.
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let mut rows = Vec::new();
+        for line in input.lines() {
+            let mut row = 0u64;
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' if x < 64 => row |= 1 << x,
+                    '#' => return Err(line.advance(x).error("shapes are at most 64 wide")),
+                    '.' => {}
+                    c => return Err(line.advance(x).error(format!("unknown tile `{c}`"))),
+                }
+            }
+            rows.push((line, row));
         }
+
+        let first = rows.iter().position(|(_, row)| *row != 0);
+        let last = rows.iter().rposition(|(_, row)| *row != 0);
+        let (Some(first), Some(last)) = (first, last) else {
+            return Err(input.error("empty shape"));
+        };
+        let rows = &rows[first..=last];
+        if let Some((line, _)) = rows.iter().find(|(_, row)| *row == 0) {
+            return Err(line.error("rocks must be in one piece"));
+        }
+
+        let all = rows.iter().fold(0, |all, (_, row)| all | row);
+        let left = all.trailing_zeros();
+        Ok(Self {
+            rows: rows.iter().rev().map(|(_, row)| row >> left).collect(),
+            width: u64::BITS - all.leading_zeros() - left,
+        })
     }
-}
 
-#[derive(Debug, Hash, PartialEq, Eq)]
-struct P(u8, usize);
+    fn parse_all(input: Input) -> Result<Vec<Self>, ParseError> {
+        input.blocks().map(Self::parse).collect()
+    }
+}
 
-struct Rock<'a> {
-    shape: &'a Shape,
-    pos: P,
+/// The settled rock, one bit mask per row from the floor up.
+#[derive(Debug, Clone)]
+struct Chamber {
+    width: u32,
+    rows: Vec<u64>,
 }
 
-impl Rock<'_> {
-    fn can_move_down(&self, rocks: &[u8]) -> bool {
-        // base case is rock is at y == 0 on the floor
-        if self.pos.1 == 0 {
-            return false;
+impl Chamber {
+    fn new(width: u32) -> Self {
+        assert!((1..=64).contains(&width), "chambers are 1 to 64 wide");
+        Self {
+            width,
+            rows: Vec::new(),
         }
-
-        !self
-            .shape
-            .get_pieces(self.pos.0)
-            .iter()
-            .enumerate()
-            .any(|(y, p)| {
-                if let Some(r) = rocks.get(y + self.pos.1 - 1) {
-                    r & p != 0
-                } else {
-                    false
-                }
-            })
     }
 
-    fn can_move_left(&self, rocks: &[u8]) -> bool {
-        if self.pos.0 == MIN_X {
-            return false;
-        }
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
 
-        !self
-            .shape
-            .get_pieces(self.pos.0 - 1)
-            .iter()
-            .enumerate()
-            .any(|(y, p)| {
-                if let Some(r) = rocks.get(y + self.pos.1) {
-                    r & p != 0
-                } else {
-                    false
-                }
-            })
+    /// Whether `shape` fits with its bottom left corner at `(x, y)`.
+    fn fits(&self, shape: &Shape, x: u32, y: usize) -> bool {
+        x + shape.width <= self.width
+            && shape
+                .rows
+                .iter()
+                .zip(y..)
+                .all(|(row, y)| self.rows.get(y).is_none_or(|r| r & (row << x) == 0))
     }
 
-    fn can_move_right(&self, rocks: &[u8]) -> bool {
-        let x = self.pos.0 + 1;
-
-        if match self.shape {
-            Shape::Flat => x + 3 == MAX_X,
-            Shape::Cross => x + 2 == MAX_X,
-            Shape::Angle => x + 2 == MAX_X,
-            Shape::Tall => x == MAX_X,
-            Shape::Block => x + 1 == MAX_X,
-        } {
-            return false;
+    fn place(&mut self, shape: &Shape, x: u32, y: usize) {
+        for (row, y) in shape.rows.iter().zip(y..) {
+            if y == self.rows.len() {
+                self.rows.push(0);
+            }
+            self.rows[y] |= row << x;
         }
+    }
 
-        !self.shape.get_pieces(x).iter().enumerate().any(|(y, p)| {
-            if let Some(r) = rocks.get(y + self.pos.1) {
-                r & p != 0
-            } else {
-                false
+    /// How far below the top each column's highest rock is, which is what
+    /// the next rocks land on. Depths stop at `SURFACE_DEPTH` so columns the
+    /// rocks rarely reach don't keep the profile from ever repeating.
+    fn surface(&self) -> Vec<usize> {
+        let mut depths = vec![SURFACE_DEPTH; self.width as usize];
+        let mut unseen = u64::MAX >> (64 - self.width);
+        for (depth, row) in self.rows.iter().rev().take(SURFACE_DEPTH).enumerate() {
+            let mut found = row & unseen;
+            unseen &= !found;
+            while found != 0 {
+                depths[found.trailing_zeros() as usize] = depth;
+                found &= found - 1;
             }
-        })
+            if unseen == 0 {
+                break;
+            }
+        }
+        depths
     }
 }
 
-#[derive(Default, Debug)]
+/// Draws the top of the chamber with `shape` falling at `x`, `y`.
+fn view(chamber: &Chamber, shape: &Shape, x: u32, y: usize) -> String {
+    let max_y = chamber.height();
+    let mut view = format!("--{max_y:0>4}--\n");
+    let max_y = max_y.max(30);
+    let min_y = max_y.saturating_sub(30);
+    for row in (min_y..max_y + 10).rev() {
+        let settled = chamber.rows.get(row).copied().unwrap_or(0);
+        let falling = row
+            .checked_sub(y)
+            .and_then(|i| shape.rows.get(i))
+            .map_or(0, |r| r << x);
+        let line = (0..chamber.width)
+            .map(|col| match (falling >> col & 1, settled >> col & 1) {
+                (1, _) => '@',
+                (_, 1) => '#',
+                _ => '.',
+            })
+            .collect::<String>();
+        writeln!(view, "|{line}|{:0>4}", row + 1).unwrap();
+        if row == 0 {
+            writeln!(view, "+{}+", "-".repeat(chamber.width as usize)).unwrap();
+        }
+    }
+    view
+}
+
+#[derive(Debug)]
 pub struct Solution {
     jets: Vec<Dir>,
     shapes: Vec<Shape>,
+    width: u32,
+    part1_rocks: u64,
+    part2_rocks: u64,
+    // redraw the top of the chamber after each rock lands
+    animate: bool,
 }
 
-// how many rows of rocks to use in cache key
-const WINDOW_SIZE: usize = 3;
-type CacheKey = ([u8; WINDOW_SIZE], usize, usize);
+impl Default for Solution {
+    fn default() -> Self {
+        Self {
+            jets: Vec::new(),
+            shapes: Shape::parse_all(Input::new(SHAPES)).unwrap(),
+            width: 7,
+            part1_rocks: 2022,
+            part2_rocks: 1_000_000_000_000,
+            animate: false,
+        }
+    }
+}
 
 impl Solution {
     fn get_rock_height(&self, target_rock_count: u64) -> u64 {
         let mut jets = self.jets.iter().enumerate().cycle();
         let mut shapes = self.shapes.iter().enumerate().cycle();
-        let mut rocks: Vec<u8> = Vec::new();
-        let mut pattern_cache: HashMap<CacheKey, (u64, u64)> = HashMap::new();
+        let mut chamber = Chamber::new(self.width);
+        let mut detector = CycleDetector::new();
 
-        let mut extra_height = 0;
         let mut rock_count = 0;
         while rock_count < target_rock_count {
             rock_count += 1;
             let (shape_index, shape) = shapes.next().unwrap();
-            let mut rock = Rock {
-                pos: P(MIN_X + 2, rocks.len() + 3),
-                shape,
-            };
-
-            // I don't know why linter complains about this
-            // not being used when it clearly is...
-            #[allow(unused_assignments)]
-            let mut jet_index = 0;
+            // rocks appear two units from the left wall, or as far left of that as
+            // a narrow chamber needs
+            let mut x = 2.min(self.width - shape.width);
+            let mut y = chamber.height() + 3;
+
+            let mut jet_index;
             loop {
                 let jet = jets.next().unwrap();
                 jet_index = jet.0;
-                match jet.1 {
-                    Dir::Left => {
-                        if rock.can_move_left(&rocks) {
-                            rock.pos.0 -= 1;
-                        }
-                    }
-                    Dir::Right => {
-                        if rock.can_move_right(&rocks) {
-                            rock.pos.0 += 1;
-                        }
-                    }
+                let pushed = match jet.1 {
+                    Dir::Left => x.checked_sub(1),
+                    Dir::Right => Some(x + 1),
+                };
+                if let Some(pushed) = pushed.filter(|x| chamber.fits(shape, *x, y)) {
+                    x = pushed;
                 }
 
-                if rock.can_move_down(&rocks) {
-                    rock.pos.1 -= 1;
+                if y > 0 && chamber.fits(shape, x, y - 1) {
+                    y -= 1;
                 } else {
                     break;
                 }
             }
+            chamber.place(shape, x, y);
 
-            let pieces = rock.shape.get_pieces(rock.pos.0);
-
-            for (y, section) in pieces.iter().enumerate() {
-                // some sections of our shapes are just padding
-                // so we can have arrays
-                if section == &0u8 {
-                    break;
-                }
-
-                let y = y + rock.pos.1;
-                if y >= rocks.len() {
-                    rocks.push(*section);
-                } else {
-                    let new_section = rocks[y] | section;
-                    rocks[y] = new_section;
-                }
+            // the detector only accepts a cycle once the height gains repeat
+            // over whole periods, so this key needn't capture everything
+            let key = (jet_index, shape_index, chamber.surface());
+            if let Some(cycle) = detector.record(rock_count, key, chamber.height() as i64) {
+                return detector.extrapolate(cycle, target_rock_count) as u64;
             }
 
-            if rocks.len() >= WINDOW_SIZE {
-                let mut last_group: [u8; WINDOW_SIZE] = [0; WINDOW_SIZE];
-                last_group.clone_from_slice(&rocks[rocks.len() - WINDOW_SIZE..]);
-
-                let key = (last_group, jet_index, shape_index);
-                if let Some((old_height, old_rock_count)) = pattern_cache.get(&key) {
-                    let rock_diff = rock_count - *old_rock_count;
-                    if rock_diff < 50 {
-                        continue;
-                    }
-
-                    let height_diff = rocks.len() as u64 - *old_height;
-                    let catchup = (target_rock_count - rock_diff) / rock_diff;
-                    extra_height += height_diff * catchup;
-                    rock_count += rock_diff * catchup;
-                }
-
-                pattern_cache.insert(key, (rocks.len() as u64, rock_count));
+            if self.animate {
+                clear_terminal();
+                print!("{}", view(&chamber, shape, x, y));
+                sleep(Duration::from_millis(140));
             }
-
-            // print_view(&rock, &rocks);
         }
 
-        rocks.len() as u64 + extra_height
+        chamber.height() as u64
     }
 }
 
@@ -224,21 +268,36 @@ impl Solver for Solution {
 
     fn with_input(&mut self, input: &str) {
         self.jets = input.chars().map(Dir::from).collect();
-        self.shapes = vec![
-            Shape::Flat,
-            Shape::Cross,
-            Shape::Angle,
-            Shape::Tall,
-            Shape::Block,
-        ];
     }
 
     fn solve_part1(&self) -> String {
-        self.get_rock_height(2022).to_string()
+        self.get_rock_height(self.part1_rocks).to_string()
     }
 
     fn solve_part2(&self) -> String {
-        self.get_rock_height(1_000_000_000_000).to_string()
+        self.get_rock_height(self.part2_rocks).to_string()
+    }
+
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "width" => {
+                let mut width = 0;
+                assign(&mut width, name, value)?;
+                let widest = self.shapes.iter().map(|s| s.width).max().unwrap_or(1);
+                if !(widest..=64).contains(&width) {
+                    return Err(ParamError::Invalid {
+                        name: name.to_string(),
+                        message: format!("chambers are {widest} to 64 wide"),
+                    });
+                }
+                self.width = width;
+                Ok(())
+            }
+            "part1_rocks" => assign(&mut self.part1_rocks, name, value),
+            "part2_rocks" => assign(&mut self.part2_rocks, name, value),
+            "animate" => assign(&mut self.animate, name, value),
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
@@ -267,6 +326,55 @@ mod test {
         assert_eq!(solution, "1514285714288");
     }
 
+    #[test]
+    fn test_shapes() {
+        let shapes = Shape::parse_all(Input::new(SHAPES)).unwrap();
+        assert_eq!(shapes.len(), 5);
+        assert_eq!(shapes[1].rows, [0b010, 0b111, 0b010]);
+        // the corner is on the right, so the bottom row comes first
+        assert_eq!(shapes[2].rows, [0b111, 0b100, 0b100]);
+        assert_eq!(
+            shapes.iter().map(|s| s.width).collect::<Vec<_>>(),
+            [4, 3, 3, 1, 2]
+        );
+        assert!(Shape::parse(Input::new("#x")).is_err());
+
+        // empty rows and columns around a rock don't move it
+        let padded = Shape::parse(Input::new("....\n..#.\n.##.\n....")).unwrap();
+        assert_eq!((padded.rows, padded.width), (vec![0b11, 0b10], 2));
+        assert!(Shape::parse(Input::new("...\n...")).is_err());
+        assert_eq!(Shape::parse(Input::new("#\n.\n#")).unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn test_wide_chamber() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        solver.set_param("width", "64").unwrap();
+        assert_eq!(solver.solve_part1(), "2440");
+        assert!(solver.set_param("width", "65").is_err());
+        assert!(solver.set_param("width", "3").is_err());
+
+        // a rock as wide as the chamber just stacks up
+        let mut chamber = Chamber::new(4);
+        let flat = Shape::parse(Input::new("####")).unwrap();
+        assert!(!chamber.fits(&flat, 1, 0));
+        chamber.place(&flat, 0, 0);
+        assert!(!chamber.fits(&flat, 0, 0));
+        assert_eq!(chamber.surface(), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_view() {
+        let mut chamber = Chamber::new(4);
+        let flat = Shape::parse(Input::new("####")).unwrap();
+        chamber.place(&flat, 0, 0);
+        let plus = Shape::parse(Input::new(".#.\n###\n.#.")).unwrap();
+        let view = view(&chamber, &plus, 1, 1);
+        assert!(view.starts_with("--0001--\n|....|0040\n"));
+        assert!(view.ends_with("|..@.|0004\n|.@@@|0003\n|..@.|0002\n|####|0001\n+----+\n"));
+    }
+
     #[test]
     fn test_solution_part1() {
         let mut solver = Solution::default();
@@ -284,35 +392,4 @@ mod test {
     }
 }
 
-#[allow(dead_code)]
-fn print_view(rock: &Rock, rocks: &Vec<u8>) {
-    clear_terminal();
-    let max_y = rocks.len();
-    println!("--{max_y:0>4}--");
-    let max_y = max_y.max(30);
-    let min_y = if max_y > 30 { max_y - 30 } else { 0 };
-    let shape = rock.shape.get_pieces(rock.pos.0);
-    for y in (min_y..max_y + 10).rev() {
-        print!("|");
-        let rocks = rocks.get(y).unwrap_or(&0u8);
-        let rock = if y >= rock.pos.1 && y < rock.pos.1 + shape.len() {
-            shape.get(y - rock.pos.1).unwrap_or(&0u8)
-        } else {
-            &0u8
-        };
-
-        let line = format!("{:#09b}", rock | rocks)
-            .trim_start_matches("0b")
-            .replace('0', ".")
-            .replace('1', "X");
-
-        print!("{line}");
-        println!("|{:0>4}", y + 1);
-        if y == 0 {
-            println!("+-------+");
-        }
-    }
-    sleep(Duration::from_millis(140));
-}
-
 const INPUT: &str = "><<<><<>>>><<><<<<>>>><<<>>><<<>>>><<<><>><<<>>>><<<><>>><<>>>><>>>><>><<<>>><<<<>><>><<>>><<<<>>>><>>><<<>><<<<><<<<>>><<>>>><><>>><<<<>><<>>>><<><<<<><<>>>><<<><<><>>>><<<><<<>>><<<>>>><<<>>><<>>>><<<><<<<>>>><<>><<<<>>>><<>>>><<<>><<>>>><>>>><<<<>>><>>>><>>>><<<<>><<<>><<<>>>><<<>>>><<<><<<>>>><>><<>><>>><<<>>><>><<<<><<<<><>>><<>>>><<<<>>>><<<<><<<>>>><><<>>><<<>>>><<<>>>><><<>>><<<>>><<<<>><<<<>>>><>><<<<><<<>>><<>>>><>><<<>>>><<<>>>><<>>>><<<><>><>>><<<>><>>>><<<<>><<<>><<><<<<>>><<<>>>><>>><<<>>><<<<>>><<>><<><<<<>>>><>><>>>><>>>><<<>>>><>>><<<>>><<<<>>><<>>>><<>>><<<>>><<>>><<<>><<<>>><<<>><>>><<>><<<><<><<<<><<<<>><>><><>>><<<><>><<<>>>><<<>>>><<<>><<<>><<<>>>><>><<<><<<><>>><<<>><<<><<<<><>>><<<<>>>><>>><<<>><><<>>>><<<>><<<>>>><<<>>><<><<>><<<<><<>>>><<<>><<<<>>>><<>>><<<<>>><<>>><<<<><><<<<>>>><<>>>><>>>><<<>>><<<<>><<>>><<<>><<>>>><>>>><<<>><<<>>><<<>><<>><<>>>><<<<>><<>><<>>>><<>>><<>>><<<>>><<<<>>><<>><<<>><>>>><<<<><<>>>><<<<>>><>>><>><>><<<<><>>>><<<<><<<<>>><<<<>>>><>>><<<<><<<>>>><<<>>><><>>>><>><>><<<<>>><<<<>>>><<<<>>><<<<>><<<>>>><<<><<>>><>><>><<>>>><>><<<<>><<><>><<<>>><><<>>>><>>>><>>>><<<>>>><<<>>>><<<>>><<>>>><<<<>><<<><<<>>>><<<<>>>><<<<>><<>><<<<>><<><>>><><<<<><><>><<>>>><<<<>>>><>>>><<<>>><<>>>><>>><<>>>><<<<><<>>>><<<>>>><>>><<>>>><>>><>><<<<><><>><<<<>>>><>>>><<>>>><<<<>>>><>>>><<><<<>><<<>><<<><<<<>>>><<>>><<>>><<><<<>>><<<>>>><<<>>>><<<<><<<<><<<>>><>>>><<<>><<<>>>><<<>>>><<<<>>><<>>>><>>>><>><<<<>><<>><>><<<<>>>><<><<<><<<<>>><<<>><<<>>><>>><<<<><<>>><<>><<>>>><<>><<><<<>><<<>>>><<><<<<>>>><<<<>>>><<<>><<<>><<<<>>>><<<>><<<<>>><>>>><<<<>><><><<>>><<<><<<>><><<<>>>><<>>>><<>>>><<<>>>><<<<>><<<<>>><<>><<<<>>><<<>><<<><<<<>><>>><<<><<<<>>>><<<<>>><<><<>><><<<<><<>><>>>><<<><<<<>>><<>><<<<>><<<>>>><<>>>><<><>>>><><<<>>>><<<<><><<<<>>><<<>>><<<><<<<>>>><<<<>>>><<<<>>>><<<<><><<<<>>><<<<>><>>><>>><<<>><>>>><<<<>><>><<<<><<<><>><<>>>><<<<><<<><<<>>><<<>>>><>>><<><<>><<<<><<<>>>><<<<>><<>>>><><<<<><><<>><<<><<<<><<>>><<>><<<>>>><<<<>>><<<>>>><<<<>>>><<<<>>><<<<>><<<<>><<<>>>><><<>>>><>>>><<<<>><><<<><><<<>>>><>>>><<>><>>>><>><<<<>>><<>>>><<>><<<<><<<<>>>><>><>>><<<<>><><<<>><<><<<<>><<>><<<<><<>><>>>><<<>><<<>>>><<<>>>><<<>>>><<<>>><<><<<><<>>><<>><<>>>><<<<>><<<<><<<<>>><>><>>>><<<<>>>><<<>>><<>>><<>>><<>><<<>><>>>><>>><>><>><<>>>><><<<<>><<<<><>>><<<>>><<<<>>><<><<>>><>><<><<<<>>>><<>>><<<<>>>><<<<><<<<>><><<<<>>><<<>><<<<>>>><<>>><<>><<<<>>><<>>><<<>><>>>><>>>><><<>><<<>>>><<<<>>>><<><<<<>>>><<<><<><<<<><<<<>>><<<<><<<<><<>><<<<>>>><<>><<<<>><<>>><<<>>>><<<><<<>><<<>>><>>><>><<<>><<>><<<>><<<>><<<<>>>><<<<>><>><<>>><<<><>><<<<>>><<<<>>><><<><<<<>>><<>>>><<<<>>>><<<<><>>>><<<<>>>><<<>>>><<<<>><<>><<>>><><>><>>><>>>><>>><>>>><<<>>><<><>>><>>><><>>>><<<><<>>>><<><<<><<>>><><<<>>><<>><<<<>><<<><>><<>><<<>><<<<>><<>><<>><>>>><>>><<<<>>>><<>>><<<>>>><<<<>>>><<><>><<>><<<<>>><>>>><>>><<>><<>>>><>>>><<>>>><<<>>><<>><<<><<<<>>><<><<>>><<<>>><><>><<<<>><<><<<>><><<<<><><<<><<<><<<<>><<>>>><<>><<<>>><<<><<<<>><<>><<<>>><<<<><>>><><<<<>>><<><<><>><>>>><>>><<<<><<>>><<<>><<<>><<<>>><>><>>>><<<>>><<<>>><><>>><<><<>><<<><<<>><<>>><<>>>><<<>>><<><>><>><<<>>><<<<>><<<><>>>><<<>>>><<>>>><<<<>>>><<<>>>><<>>>><<<><<<><<<>><>>>><<>>><>>>><<>>><<<>>>><>>>><<<<>>>><>>>><<<><<<<>><<<>>>><<<>>><<<>>>><<<<>><<<>><<<>>>><<<<>>>><<<<>>>><<<>>><>>><>>>><<<<>><<>>><>>>><>>><>>><<<>>><<>>>><<<<>>>><>>><<<>><<>><<><<<>>>><>>><<><>>><<>>><<>>><<<<>><<<>>>><<<><<<<><<><<<<>>><<>>>><<<>><>><<>>><<<<>>>><<>>>><<<<>>>><<<>>><<<>>>><>>><<><<<<>><<<><<<<>>>><>>><<<<>>><<<<>><<<>>>><>><<<<><<<><<<>>>><<<>>><>>>><<<<>><<<<>>><<>>>><>>>><<<>>>><<><>>><<<><<<<><<<<>><><<<<>><>>><<>>><<<>><<<<>>>><<<<>><<<<><<<<>><<<>>>><<<<>>><<<>><<<>><<<<>><>>>><>><<<>>>><<<<>>>><<><<<>><><<<><<>>><>>>><><<<<>>>><<>><<<>>>><<<<>><<<<><>>>><<<>>><<<<><<<><>><<<>>><>><<<><<<<><<<>>>><<<<>>>><<<>>>><<<<><>><<<<>>><<<><<>>><>><<>><<<<>><<>>>><>><<>><<<><<>>><<>><<<<>><<<<>>>><<>>><<<>>><<><>>>><<>>>><<><<<>><<<>>><>><><<<<>>>><>>><<>><<><<<<>>>><<>>>><<<>><<<>>><<<<>>>><>>>><<>><>>><<<>><>><>>>><<<<><<>>>><>>>><><>>><<<>><<<>><<<><>>>><<>>>><<<<>>><<><<>>>><<<<>>>><><<<>><<<>>><<<>>>><<>>>><<<<><<<<>>><<<<>><>>><<<>>>><>>><<<<>>><<>>><<<>>>><<><<>>><<<><<<>>>><<<>>><<<><><<<<>><<<>>>><<><<<<><>>>><><<<>>><<<>><<<>>><>>><<<>>>><<><<<><<>>><<>><<>>>><<<>><<>>>><<>><<<>>>><<<>><<<>><><<><<<>><<<>>><>><>>>><<>><<>><<>><<<>>><<<<>><<>>>><<<>><<<><<><<<>>><>><<<>><<>>>><<<<>><<<>><<<>>><<>>><<>>><<<><>><<>>><<>><<<<>>><<><>>><<<>>><<>>>><<<<>><<<<>><>>><><<>>><>>>><<><<<<><<<<><>>>><<>>>><<<<>>><>>><<><<<><>>><<<>>><<<<>>>><<<><>><<<<>><<>>><<<<><>>><>>><<<<>>>><>>><<>><<<>>>><>>>><<>>>><<<<>>><<<>><>>>><<<><><<<<><<<<>>>><<<>>><><<><<<>><>><>><<<<>>><<><<<<>>><<>>>><>>><<><<<<>><<>>>><<><<<<>><<>><>><<<><<><<<>>>><<<<><<<<>>><<>>>><<<<>>><<<<>>>><<<>>>><><<<><>>>><>>><<>>>><<<>>>><>>>><>>><>><>>><>>><<<>>><>>>><>><<<<><<<><<<<>>><<<<>>><<<<>><<<><<<>><<<>>><<<>>>><<>><<>>>><<>>>><><<><><<<<><>><<<<><<<<>>>><<>>>><<<<>>><<><<<>>>><>>><<><<>>><<<<>><>>><<<>>><<>><<<<>>><<<<>>><><<<><><<<>><>>><<>>>><<>><<<<>>><<<<><<<><>><<<<>><><><<>><>>>><<<<><<<>>>><<<<>>>><<>><<<<><><<><>>>><<<>>><<>>>><<<><>>><><<<>>>><<<<><<<<>><<<>><>><<<<>>>><<<<>>><<<>><>><<>>><><<<>>>><<<<>>><<><>>><<>><<<><><<<<>>>><<<>>><<>>>><><>><><<<<>><<<<>>>><>>><><<<>>>><>><><<<<>>>><>>><<<<>><<<<>>>><<><>>>><<<<>><<<<>>>><<<>>><<<<><<<<>><>>><<>>>><<>>>><<<><<>>>><<<>>>><><>>>><<<<>><<<>><<>>><<<<><<<>>>><<>><<<><<><<<<>><<<>><><>>>><<>><<<>>>><<<<>>><<>><<<<><<>><>>><><<<<><>>>><<<>>>><<<<>><>>>><><>><<>>><<<>>>><>><<><<<<>>>><<<>><<<>><<>>><<><<<<>>><<<>><<<>>><<>>>><<>>>><<<<>>>><>><<<><<>><<>>><<<<>><<<>><<<<>>><><>>>><<>>>><>>><>><<<<>>><>>><>>><<<><<>>>><<<>>>><<>>><<<>>><<<>><<<<><>>>><<<>><<<>><<<<>><>>>><<><<<<><>>><<<<>>><<>>><<<<>>><<<><>><<<>>><<>><<<<>>>><<>>><<<<>>>><<<<><<<>><><<<>>>><<<<>>>><<<<>>><<>>>><<>><<>><<<>><<<>><<<<>><><>><>><<<><>>>><<<><<<>><<<><<<>>><<<><<<<>>>><><<>>><><<<<><<<<>>>><<<><<<>><<><<>><<<>>>><><<>>>><<<<>><>><><>>>><<<>>>><<>>>><<>>>><>>><>>><<<<><<<<><<<<><<<<>>><><<<<>>>><><>>>><<<><><<>>><<<<><<<>><<<<>><<>>><><<<<>><><<<>>>><>>>><>>><<<>><>><<<<>><>><<<>>>><<><<>><>><<<<>>><<>><<>>>><<<<>><<<<>><>><<<>>><>><><<<<>><<<<><>><<<>>>><<<<><>>>><<<<>><>>>><>>><<<>>><<>>><>>><<<<>><<><<<>>><>><>>><>>><<<<>>>><<><>>><<>>>><<<<>>>><<>><<<>><>><<<<>>>><<<>><<>><>><>><><<><>><><<>>><<<>><>>>><<<<><<<<><<<<>>><<<<>>>><<<<>>>><<<<>>>><<>><>><<<><>>><<<>>>><<<>>><<<><<>>>><>><<>>>><<><>>><<>><<<<>><>><<<<>>>><<<>>>><<<>>><<<<>>>><<><<>>>><><<>>><>><<>><<<<>>>><<><>><>><<<<>><>><<<<>>>><><<<<><>><<<>>>><<<<>>>><<>>>><<>>><<<<>>><>>>><<><<<<>><>><>><<<<>>>><<>>>><<<<>>><<><<<>><<<>>>><<>>>><>>><>><<<>>><<>>>><><<<<>>>><<<>>>><<><<<>>><><><<<>><<>>>><<<>><<><<<<>><>>><>>><><<>>><<>><<>>>><<>><><<>>><>><>>><>>>><<<<>>>><<<<>><>><<<<>><<<<><<>>>><>>>><<>><<<>>><><<<<>>>><<<<><<><<<<>>><<><>><<<<>><<>><>>>><><<<>>>><<<<>>><<<>>>><<>><<<>>><<<<><>>>><<<<>>>><<<<><<<<>>><<<>><><<<<>><<>>><<<>>>><<>>>><<<><<>><<<<><<><>>>><<<<><>><<<>><<<<>><>><<<>>>><<>><><<<>>><<<<>>>><>>>><<>><<<<><<>>>><<>>><>>><<>>>><>>><<<<>>><<<>>><<<>><<<>>><<>>><<<<>>>><<<>><<<<>>>><<<><<<<>>><<<<>><<<<>>><<<<>><>><<<>>>><>>><<>>>><<>>><<>><>>><<<<>><>>>><<<>>>><<<>>><<<<><>>><>><<>>><>>>><<<>><<>><<>>><<<><>>><<>>><>>><<>>>><>>><<<<><>>>><>><<<>><<<<>><<>>><<>>>><<<>><<<>>><<>>>><<<><<<<>>>><<<>><>>><<<>><<>>><>>><<<>>>><<><<>>>><>><<<<>>>><<><<<>>>><<<<>>><<><<<>>>><<<>><<>><<<<>>><>>>><>>>><<>>><><<<<>>>><<<<>>><<<><<<<>><<<<>><<<<>><<<>><<<<>><>>>><<<><<<<><><<>>>><<>><>><<<<>>><<<>>><<<<>>>><<<>>>><<<>><<<<><<<>>>><<>>><<>><<<><<>>>><<<<>>><>>><<<>>>><>><<>>><<<<>>><<<>>>><><<>><<>><<<<><<>><>>><<<>>><<>>>><<<<>>><<<>>><<<>>><<><<<<>>>><<<>>><><<<><>><<>>>><><>><><<>>><>>><<<<>><<<<>><>>>><><<<><><><<>><>>><<>>><<<<>>>><<><<<<>><<<><>>>><>>><<>>>><>>><><><<<>>>><<<<>>>><<>><<<<>><<><>><>>>><>><<<>>>><><<<>><<<>><<>><<<>>><><>><><<>>><<<>><<<><<<<>>>><>>>><<<<><>>><<<<>>>><>>>><<<>>>><<<<>><<<>>><<><>>><<<<><<>>><<<<><<>>>><<<<>>><>>><<<><<<>><>>><<<<>>>><<<<>>><<>>>><<<<>>>><<<>>><<<<><<<>>><>>>><<><>>><<<>>>><>><<<<><>><>>>><>><<>><>><<>>><<<<>>><>>>><<<<>><<><>>><<<<>>><>>>><>>><<>><<<<>><<><<<>>>><>>><<><<>>>><<>>><<>>>><>>><>>>><<<<><<<<>>><<<><<>>>><<<<>><<<>><<><<><<><<<><<<<><>><<>>>><<<<>>><<<>>><<<<>>><<<><<<>>><<<>>>><<<>>><<<<>><<<<>><<><<<<>>>><<<>>><<>>><>><>><<<><<<<>>><<><<<>>><<<<>>>><>>><<<>>>><<<>><<>>><<<>>><<<>><>><>>>><<<<>>>><<<>><>><<<>><<<<>>>><<>>>><<<<><<><>>><<>>><<<<>>>><<<<><<>>><<<>><>><<<<>>>><<<<>><<<><<><<><><<<<><<>>><<<><<>><<<<><<<<><<<>><>><<<<>>>><<<>><<<<><<>><<<>>>><<>><<>><>><<<<>><>>>><<<<>><<<>><<>>>><<><<<>><<<>>>><<>>>><<<<>><>>><<<<>><>>><<<>>><<>>>><<<<>><<<><>><<><<<>><<>>>><<<>>><>><>>>><<>><>><<>><<<><<<<>>>><<<<>>>><<>>>><<<<>>>><>>>><<>>><>>>><<>>><<<<>><<<<>><<<>><<>>><<<<>>>><<<<>>>><<<>>>><<>><<<>>><<>>>><<>><<>>>><<>>>><<><<<<>><<<>>><<<><<>>><<>><<<<><<>>><<<<>>>><<><<<<><>><<>>><<<<>>>><<<<><<<>>><><>>>><>><<<><>>>><<<>>>><>>><<><<>><><<<<>>>><<>>>><<<><<>>>><>>>><<<<><<<<>>>><<><>>>><>>><><<>>><<<>>><<<<><>><<<>><<<><<<><<<<>><<<>>><<<<>>>><<<>>>><<<>>><<<>>><>>><<>><<<>>><<><<<><>>>><>>><<<<>>><<><<<<>>>><<<><<<<>><<>>>><<>>><<<<>>>><<>>><><<<>>><<>><<>><<>>>><<<<><<<>>><<<<>>><<<<>>>><<<>>>><<<<>>><<<><>>><>>><<>>>><>>><<>>><<>><<>>>><<>>><<<<>>>><<<<>><<<>>>><<<<>>>><<<<>><<<><>><<>><>>><<<<><<<>><><<<>>>><<<>>><<>>><<>>>><<<<>>>><<<><<<>>><<<>><<<>><<<<>>><<<<>><<<>>><<<><<<><<>><><>><<<>>><<<>>><<>>><<<>>><<>>><<<<>><<<>><>><<<<>><<<<>><<>><<<>><<<<>>>><<<><<<<><<<<>><>>><<>><<><<>>><<<><<>>><<<><<<<>><>>><<>><>>><<>>>><<>>>><<<<>><<><<>>><<<>>>><<><<<<>>>><><<<<>>><<<<>><<<<>><<>>><<<<>>>><>>><<<<>><<<<>><><<<<>>>><<<>>><<>><<<<>>><<<><<<>>><>><<>>>><<>>><>><>><<<<>>>><><<<<><>>><<><<>><<<>>><>><<<>>>><<<<>>><<<>><<<<>>>><>><<<>>>><<<>>><<>>>><<>><<<<>>><<>>><<<>><<><>><>><<>><<<><><>>><<<>><<<>><<<<>>><>>><><<<>><<<>>>><<<><<>>>><>>>><>><>>><<<<><<<>><<<<>><<><<<<>>>><<>><<>>>><<<><<<>>><<<<>>><<<>>>><<>>>><<<>>><<<<>><<<>>><>>><>>>><<<>><<<<><<<<><><<<>>><><<<<>><<<>>>><<<<>>>><<><<><<<><<<><><<>>";
@@ -1,21 +1,30 @@
-use std::{
-    collections::{HashSet, VecDeque},
-    i8,
-    ops::Add,
+use std::{fmt::Write, ops::Add};
+
+use crate::{
+    params::{assign, assign_file, ParamError},
+    parse::{complete, pattern, Input, ParseError},
+    solver::Solver,
+    utils::{write_output, FxHashMap},
 };
 
-use crate::solver::Solver;
+#[derive(Debug, Default, PartialEq, Eq, Hash, Copy, Clone)]
+struct Pos(i32, i32, i32);
+
+impl Pos {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let [x, y, z] = complete(pattern("{},{},{}"), input)?;
+        Ok(Self(x.parse()?, y.parse()?, z.parse()?))
+    }
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Pos(i8, i8, i8);
+    /// One step along `axis` (0 for x, 1 for y, 2 for z) in the direction of `sign`.
+    fn unit(axis: usize, sign: i32) -> Self {
+        let mut unit = [0; 3];
+        unit[axis] = sign;
+        Self(unit[0], unit[1], unit[2])
+    }
 
-impl From<&str> for Pos {
-    fn from(value: &str) -> Self {
-        let d = value
-            .split(',')
-            .map(|n| n.parse().unwrap())
-            .collect::<Vec<i8>>();
-        Self(d[0], d[1], d[2])
+    fn neighbors(self) -> impl Iterator<Item = Self> {
+        (0..3).flat_map(move |axis| [1, -1].map(|sign| self + Self::unit(axis, sign)))
     }
 }
 
@@ -26,110 +35,293 @@ impl Add for Pos {
     }
 }
 
-// impl std::ops::AddAssign for Pos {
-//     fn add_assign(&mut self, rhs: Self) {
-//         self.0 += rhs.0;
-//         self.1 += rhs.1;
-//         self.2 += rhs.2;
-//     }
-// }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Voxel {
+    Lava,
+    Air,
+    /// air the steam can reach from outside the droplet
+    Outside,
+    /// air sealed inside the droplet, tagged with its pocket's index
+    Pocket(usize),
+}
 
-#[derive(Default, Debug)]
-pub struct Solution {
-    input: HashSet<Pos>,
+/// One face of a lava cube, pointing away from it along `axis`.
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    cube: Pos,
+    axis: usize,
+    sign: i32,
 }
 
-impl Solver for Solution {
-    fn get_input(&self) -> &'static str {
-        INPUT
+impl Face {
+    fn normal(&self) -> Pos {
+        Pos::unit(self.axis, self.sign)
     }
 
-    fn with_input(&mut self, input: &str) {
-        self.input = input.lines().map(|l| l.into()).collect();
+    /// The face's corners, counter-clockwise when looking at it from outside.
+    fn corners(&self) -> [Pos; 4] {
+        let base = if self.sign > 0 {
+            self.cube + self.normal()
+        } else {
+            self.cube
+        };
+        let u = Pos::unit((self.axis + 1) % 3, 1);
+        let v = Pos::unit((self.axis + 2) % 3, 1);
+        let corners = [base, base + u, base + u + v, base + v];
+        if self.sign > 0 {
+            corners
+        } else {
+            [corners[0], corners[3], corners[2], corners[1]]
+        }
     }
+}
 
-    fn solve_part1(&self) -> String {
-        let neighbors = [
-            Pos(1, 0, 0),
-            Pos(0, 1, 0),
-            Pos(0, 0, 1),
-            Pos(-1, 0, 0),
-            Pos(0, -1, 0),
-            Pos(0, 0, -1),
-        ];
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pocket {
+    /// the pocket's lowest cube, by x then y then z
+    first: Pos,
+    size: usize,
+}
+
+/// Most voxels [`Droplet::new`] will scan a droplet into, padding included.
+const MAX_VOXELS: usize = 1 << 24;
+
+/// The droplet scanned into a dense grid with a layer of air all around it,
+/// so that the outside is one connected region.
+#[derive(Debug, Default)]
+struct Droplet {
+    cubes: Vec<Pos>,
+    origin: Pos,
+    dims: [usize; 3],
+    voxels: Vec<Voxel>,
+    pockets: Vec<Pocket>,
+}
 
-        self.input
+impl Droplet {
+    /// Scans `cubes` into a grid, failing if the grid would be too big to
+    /// hold or its padding would run past the edge of `i32`.
+    fn new(mut cubes: Vec<Pos>) -> Result<Self, String> {
+        let mut min = Pos(i32::MAX, i32::MAX, i32::MAX);
+        let mut max = Pos(i32::MIN, i32::MIN, i32::MIN);
+        for p in &cubes {
+            min = Pos(min.0.min(p.0), min.1.min(p.1), min.2.min(p.2));
+            max = Pos(max.0.max(p.0), max.1.max(p.1), max.2.max(p.2));
+        }
+        if cubes.is_empty() {
+            (min, max) = (Pos::default(), Pos::default());
+        }
+
+        // the flood fill looks one past the padding on either side
+        let (min, max) = ([min.0, min.1, min.2], [max.0, max.1, max.2]);
+        let fits =
+            |axis: usize| min[axis].checked_sub(2).is_some() && max[axis].checked_add(2).is_some();
+        if !(0..3).all(fits) {
+            return Err("cubes must be at least 2 away from the edge of i32".to_string());
+        }
+        let origin = Pos(min[0] - 1, min[1] - 1, min[2] - 1);
+        let dims = [0, 1, 2].map(|axis| (max[axis] as i64 - min[axis] as i64 + 3) as usize);
+        let voxels = dims
             .iter()
-            .map(|p| {
-                6 - neighbors
-                    .iter()
-                    .filter(|v| self.input.contains(&(*p + **v)))
-                    .count()
-            })
-            .sum::<usize>()
-            .to_string()
+            .try_fold(1usize, |product, d| product.checked_mul(*d))
+            .filter(|voxels| *voxels <= MAX_VOXELS)
+            .ok_or_else(|| {
+                let [dx, dy, dz] = dims;
+                format!("the droplet's {dx}x{dy}x{dz} bounding box is too big to scan")
+            })?;
+
+        let mut droplet = Self {
+            cubes: Vec::new(),
+            origin,
+            dims,
+            voxels: vec![Voxel::Air; voxels],
+            pockets: Vec::new(),
+        };
+        // keep only the first of any repeated cube, so no face counts twice
+        cubes.retain(|&cube| {
+            let index = droplet.index(cube).unwrap();
+            let first = droplet.voxels[index] != Voxel::Lava;
+            droplet.voxels[index] = Voxel::Lava;
+            first
+        });
+        droplet.cubes = cubes;
+        droplet.classify();
+        Ok(droplet)
     }
 
-    fn solve_part2(&self) -> String {
-        let mut min_x = i8::MAX;
-        let mut max_x = i8::MIN;
-        let mut min_y = i8::MAX;
-        let mut max_y = i8::MIN;
-        let mut min_z = i8::MAX;
-        let mut max_z = i8::MIN;
+    fn index(&self, p: Pos) -> Option<usize> {
+        let [x, y, z] = [
+            p.0 - self.origin.0,
+            p.1 - self.origin.1,
+            p.2 - self.origin.2,
+        ];
+        let [dx, dy, dz] = self.dims;
+        if x < 0 || y < 0 || z < 0 || x as usize >= dx || y as usize >= dy || z as usize >= dz {
+            return None;
+        }
+        Some((x as usize * dy + y as usize) * dz + z as usize)
+    }
 
-        self.input.iter().for_each(|p| {
-            min_x = min_x.min(p.0);
-            max_x = max_x.max(p.0);
-            min_y = min_y.min(p.1);
-            max_y = max_y.max(p.1);
-            min_z = min_z.min(p.2);
-            max_z = max_z.max(p.2);
-        });
+    fn pos(&self, index: usize) -> Pos {
+        let [_, dy, dz] = self.dims;
+        let (x, y, z) = (index / (dy * dz), index / dz % dy, index % dz);
+        self.origin + Pos(x as i32, y as i32, z as i32)
+    }
 
-        let mut outside: HashSet<Pos> = HashSet::new();
+    fn voxel(&self, p: Pos) -> Voxel {
+        self.index(p).map_or(Voxel::Outside, |i| self.voxels[i])
+    }
 
-        let neighbors = [
-            Pos(1, 0, 0),
-            Pos(0, 1, 0),
-            Pos(0, 0, 1),
-            Pos(-1, 0, 0),
-            Pos(0, -1, 0),
-            Pos(0, 0, -1),
-        ];
+    /// Flood fills the outside from a corner of the padding, then every other
+    /// region of air as a pocket. Voxels are marked as they're queued, so each
+    /// one is visited once.
+    fn classify(&mut self) {
+        self.flood(0, Voxel::Outside);
+        for start in 0..self.voxels.len() {
+            if self.voxels[start] == Voxel::Air {
+                let size = self.flood(start, Voxel::Pocket(self.pockets.len()));
+                let first = self.pos(start);
+                self.pockets.push(Pocket { first, size });
+            }
+        }
+    }
 
-        let mut outer_queue: VecDeque<Pos> = VecDeque::new();
-        outer_queue.push_back(Pos(min_x - 1, min_y - 1, min_z - 1));
+    fn flood(&mut self, start: usize, fill: Voxel) -> usize {
+        let mut stack = vec![start];
+        self.voxels[start] = fill;
+        let mut size = 0;
+        while let Some(index) = stack.pop() {
+            size += 1;
+            for next in self.pos(index).neighbors() {
+                if let Some(next) = self.index(next) {
+                    if self.voxels[next] == Voxel::Air {
+                        self.voxels[next] = fill;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        size
+    }
+
+    /// Every face of every cube, whatever is on the other side of it.
+    fn faces(&self) -> impl Iterator<Item = Face> + '_ {
+        self.cubes.iter().flat_map(|&cube| {
+            (0..3).flat_map(move |axis| [1, -1].map(|sign| Face { cube, axis, sign }))
+        })
+    }
+
+    fn surface_area(&self) -> usize {
+        self.faces()
+            .filter(|face| self.voxel(face.cube + face.normal()) != Voxel::Lava)
+            .count()
+    }
+
+    fn exterior_faces(&self) -> impl Iterator<Item = Face> + '_ {
+        self.faces()
+            .filter(|face| self.voxel(face.cube + face.normal()) == Voxel::Outside)
+    }
+
+    /// The exterior surface as a Wavefront OBJ, one quad per face with the
+    /// corners shared between faces.
+    fn obj(&self) -> String {
+        let mut vertices = FxHashMap::default();
+        let mut faces = String::new();
+        let mut obj = String::from("# advent of code 2022 day 18\no droplet\n");
+        for face in self.exterior_faces() {
+            faces.push('f');
+            for corner in face.corners() {
+                let next = vertices.len() + 1;
+                let index = *vertices.entry(corner).or_insert_with(|| {
+                    writeln!(obj, "v {} {} {}", corner.0, corner.1, corner.2).unwrap();
+                    next
+                });
+                write!(faces, " {index}").unwrap();
+            }
+            faces.push('\n');
+        }
+        obj + &faces
+    }
 
-        while let Some(q) = outer_queue.pop_front() {
-            outside.insert(q);
-            for n in neighbors.iter() {
-                let next_outer = q + *n;
-                if min_x - 1 <= next_outer.0
-                    && next_outer.0 <= max_x + 1
-                    && min_y - 1 <= next_outer.1
-                    && next_outer.1 <= max_y + 1
-                    && min_z - 1 <= next_outer.2
-                    && next_outer.2 <= max_z + 1
-                    && !self.input.contains(&next_outer)
-                    && !outside.contains(&next_outer)
-                    && !outer_queue.contains(&next_outer)
-                {
-                    outer_queue.push_back(next_outer);
+    /// The exterior surface as an ASCII STL, two triangles per face.
+    fn stl(&self) -> String {
+        let mut stl = String::from("solid droplet\n");
+        for face in self.exterior_faces() {
+            let n = face.normal();
+            let [a, b, c, d] = face.corners();
+            for triangle in [[a, b, c], [a, c, d]] {
+                writeln!(stl, "  facet normal {} {} {}", n.0, n.1, n.2).unwrap();
+                stl.push_str("    outer loop\n");
+                for v in triangle {
+                    writeln!(stl, "      vertex {} {} {}", v.0, v.1, v.2).unwrap();
                 }
+                stl.push_str("    endloop\n  endfacet\n");
             }
         }
+        stl.push_str("endsolid droplet\n");
+        stl
+    }
+}
 
-        self.input
-            .iter()
-            .map(|p| {
-                neighbors
-                    .iter()
-                    .filter(|v| outside.contains(&(*p + **v)))
-                    .count()
-            })
-            .sum::<usize>()
-            .to_string()
+#[derive(Default, Debug)]
+pub struct Solution {
+    droplet: Droplet,
+    // list the air pockets sealed inside the droplet
+    pockets: bool,
+    // write the exterior surface to this file, as OBJ or STL by its extension
+    mesh: String,
+}
+
+impl Solution {
+    fn export(&self) {
+        // set_param only accepts these two
+        let mesh = if self.mesh.ends_with(".obj") {
+            self.droplet.obj()
+        } else {
+            self.droplet.stl()
+        };
+        write_output(&self.mesh, &mesh);
+    }
+}
+
+impl Solver for Solution {
+    fn get_input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn with_input(&mut self, input: &str) {
+        let cubes = Input::new(input)
+            .lines()
+            .map(Pos::parse)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        self.droplet = Droplet::new(cubes).unwrap();
+    }
+
+    fn solve_part1(&self) -> String {
+        self.droplet.surface_area().to_string()
+    }
+
+    fn solve_part2(&self) -> String {
+        if self.pockets {
+            for Pocket { first, size } in &self.droplet.pockets {
+                println!(
+                    "pocket at {},{},{} of size {size}",
+                    first.0, first.1, first.2
+                );
+            }
+        }
+        if !self.mesh.is_empty() {
+            self.export();
+        }
+        self.droplet.exterior_faces().count().to_string()
+    }
+
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "pockets" => assign(&mut self.pockets, name, value),
+            "mesh" => assign_file(&mut self.mesh, name, value, &["obj", "stl"]),
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
@@ -170,6 +362,113 @@ mod test {
         assert_eq!(solution, "58");
     }
 
+    #[test]
+    fn test_pockets() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let droplet = &solver.droplet;
+        assert_eq!(
+            droplet.pockets,
+            [Pocket {
+                first: Pos(2, 2, 5),
+                size: 1
+            }]
+        );
+        assert_eq!(droplet.voxel(Pos(2, 2, 5)), Voxel::Pocket(0));
+        assert_eq!(droplet.voxel(Pos(0, 0, 0)), Voxel::Outside);
+
+        // coordinates beyond i8, and a hollow shell with a 2x1x1 cavity
+        let mut cubes = Vec::new();
+        for x in 999..1003 {
+            for y in -200..-197 {
+                for z in 0..3 {
+                    if (x, y, z) != (1000, -199, 1) && (x, y, z) != (1001, -199, 1) {
+                        cubes.push(Pos(x, y, z));
+                    }
+                }
+            }
+        }
+        let droplet = Droplet::new(cubes).unwrap();
+        assert_eq!(
+            droplet.pockets,
+            [Pocket {
+                first: Pos(1000, -199, 1),
+                size: 2
+            }]
+        );
+        assert_eq!(droplet.surface_area(), 2 * (12 + 12 + 9) + 10);
+        assert_eq!(droplet.exterior_faces().count(), 2 * (12 + 12 + 9));
+    }
+
+    #[test]
+    fn test_mesh() {
+        let droplet = Droplet::new(vec![Pos(0, 0, 0)]).unwrap();
+        let repeated = Droplet::new(vec![Pos(0, 0, 0); 3]).unwrap();
+        assert_eq!(repeated.obj(), droplet.obj());
+        assert_eq!(repeated.surface_area(), 6);
+        let obj = droplet.obj();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 8);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 6);
+        assert!(obj.contains("\nv 1 1 1\n"));
+
+        let stl = droplet.stl();
+        assert_eq!(stl.matches("facet normal").count(), 12);
+        assert!(stl.starts_with("solid droplet\n  facet normal 1 0 0\n"));
+        assert!(stl.ends_with("endsolid droplet\n"));
+
+        // every face winds counter-clockwise around its outward normal
+        for face in droplet.exterior_faces() {
+            let [a, b, c, _] = face.corners();
+            let (u, v) = (
+                Pos(b.0 - a.0, b.1 - a.1, b.2 - a.2),
+                Pos(c.0 - b.0, c.1 - b.1, c.2 - b.2),
+            );
+            let cross = Pos(
+                u.1 * v.2 - u.2 * v.1,
+                u.2 * v.0 - u.0 * v.2,
+                u.0 * v.1 - u.1 * v.0,
+            );
+            assert_eq!(cross, face.normal());
+        }
+
+        let example = Droplet::new(
+            "2,2,2 1,2,2 3,2,2 2,1,2 2,3,2 2,2,1 2,2,3 2,2,4 2,2,6 1,2,5 3,2,5 2,1,5 2,3,5"
+                .split(' ')
+                .map(|p| Pos::parse(Input::new(p)).unwrap())
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(
+            example
+                .obj()
+                .lines()
+                .filter(|l| l.starts_with("f "))
+                .count(),
+            58
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let error = Droplet::new(vec![Pos(0, 0, 0), Pos(i32::MAX - 1, 0, 0)]).unwrap_err();
+        assert_eq!(error, "cubes must be at least 2 away from the edge of i32");
+        let error = Droplet::new(vec![Pos(0, 0, 0), Pos(2_000_000_000, 0, 0)]).unwrap_err();
+        assert_eq!(
+            error,
+            "the droplet's 2000000003x3x3 bounding box is too big to scan"
+        );
+        assert!(Droplet::new(vec![Pos(-1000, 0, 0), Pos(1000, 1000, 0)]).is_ok());
+
+        let mut solver = Solution::default();
+        let error = solver.set_param("mesh", "droplet.png").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value for `mesh`: expected a .obj or .stl file"
+        );
+        solver.set_param("mesh", "droplet.stl").unwrap();
+        assert_eq!(solver.mesh, "droplet.stl");
+    }
+
     #[test]
     fn test_solution_part1() {
         let mut solver = Solution::default();
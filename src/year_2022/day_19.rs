@@ -1,22 +1,27 @@
-use crate::solver::Solver;
+use crate::{
+    parse::{
+        complete, literal, many, pair, preceded, separated, spanned, terminated, unsigned, word,
+        Input, ParseError,
+    },
+    solver::Solver,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum Mineral {
     Ore,
     Clay,
     Obsidian,
     Geode,
-    Unknown,
 }
 
-impl From<&str> for Mineral {
-    fn from(value: &str) -> Self {
-        match value {
-            "ore" => Mineral::Ore,
-            "clay" => Mineral::Clay,
-            "obsidian" => Mineral::Obsidian,
-            "geode" => Mineral::Geode,
-            _ => Mineral::Unknown,
+impl Mineral {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        match input.as_str() {
+            "ore" => Ok(Mineral::Ore),
+            "clay" => Ok(Mineral::Clay),
+            "obsidian" => Ok(Mineral::Obsidian),
+            "geode" => Ok(Mineral::Geode),
+            other => Err(input.error(format!("unknown mineral `{other}`"))),
         }
     }
 }
@@ -27,45 +32,141 @@ struct Robot {
     cost: Vec<(Mineral, u8)>,
 }
 
-impl From<&str> for Robot {
-    fn from(value: &str) -> Self {
-        let (ore, cost) = value
-            .trim_start_matches(" Each ")
-            .split_once(" robot costs ")
-            .unwrap();
-        Self {
-            ore: ore.into(),
-            cost: cost
-                .split(" and ")
-                .map(|c| {
-                    let (amt, ore) = c.split_once(' ').unwrap();
-                    (ore.into(), amt.parse().unwrap())
-                })
-                .collect(),
-        }
-    }
-}
-
 #[derive(Debug)]
 struct Blueprint {
     idx: u8,
     robots: Vec<Robot>,
 }
 
-impl From<&str> for Blueprint {
-    fn from(value: &str) -> Self {
-        let (idx, robots) = value
-            .trim_start_matches("Blueprint ")
-            .split_once(':')
-            .unwrap();
+impl Blueprint {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let cost = pair(unsigned(), preceded(literal(" "), spanned(word())));
+        let robot = preceded(
+            literal(" Each "),
+            pair(
+                spanned(word()),
+                preceded(
+                    literal(" robot costs "),
+                    terminated(separated(cost, literal(" and ")), literal(".")),
+                ),
+            ),
+        );
+        let (idx, robots) = complete(
+            pair(
+                preceded(literal("Blueprint "), terminated(unsigned(), literal(":"))),
+                many(robot),
+            ),
+            input,
+        )?;
 
-        Self {
-            idx: idx.parse().unwrap(),
+        Ok(Self {
+            idx,
             robots: robots
-                .trim_end_matches('.')
-                .split('.')
-                .map(|r| r.into())
-                .collect(),
+                .into_iter()
+                .map(|(ore, cost)| {
+                    Ok(Robot {
+                        ore: Mineral::parse(ore)?,
+                        cost: cost
+                            .into_iter()
+                            .map(|(amt, ore)| match Mineral::parse(ore)? {
+                                // nothing spends geodes, and costs() has no
+                                // room for them
+                                Mineral::Geode => Err(ore.error("robots can't cost geodes")),
+                                mineral => Ok((mineral, amt)),
+                            })
+                            .collect::<Result<_, ParseError>>()?,
+                    })
+                })
+                .collect::<Result<_, ParseError>>()?,
+        })
+    }
+}
+
+impl Blueprint {
+    /// How much ore, clay and obsidian each kind of robot costs, indexed by
+    /// the mineral the robot collects.
+    fn costs(&self) -> [[u32; 3]; 4] {
+        let mut costs = [[0; 3]; 4];
+        for robot in &self.robots {
+            for (mineral, amount) in &robot.cost {
+                costs[robot.ore as usize][*mineral as usize] += *amount as u32;
+            }
+        }
+        costs
+    }
+
+    fn max_geodes(&self, minutes: u32) -> u32 {
+        let costs = self.costs();
+        let mut max_robots = [0; 3];
+        for cost in &costs {
+            for (max, amount) in max_robots.iter_mut().zip(cost) {
+                *max = (*max).max(*amount);
+            }
+        }
+
+        let mut search = Search {
+            costs,
+            max_robots,
+            best: 0,
+        };
+        search.run(minutes, [1, 0, 0], [0; 3], 0);
+        search.best
+    }
+}
+
+/// Depth-first branch and bound over which robot to build next. Geode robots
+/// are credited with everything they will ever crack as soon as they are
+/// built, so only ore, clay and obsidian need tracking.
+struct Search {
+    costs: [[u32; 3]; 4],
+    // spending is capped at one robot per minute, so there's no point
+    // collecting a mineral faster than the most expensive recipe needs it
+    max_robots: [u32; 3],
+    best: u32,
+}
+
+impl Search {
+    fn run(&mut self, time_left: u32, robots: [u32; 3], stock: [u32; 3], geodes: u32) {
+        self.best = self.best.max(geodes);
+
+        // even a new geode robot every remaining minute can't beat the best
+        if geodes + time_left * time_left.saturating_sub(1) / 2 <= self.best {
+            return;
+        }
+
+        for kind in (0..4).rev() {
+            if kind < 3 && robots[kind] >= self.max_robots[kind] {
+                continue;
+            }
+
+            // skip straight to the minute this robot can be afforded
+            let Some(wait) = (0..3)
+                .map(|m| {
+                    let missing = self.costs[kind][m].saturating_sub(stock[m]);
+                    match (missing, robots[m]) {
+                        (0, _) => Some(0),
+                        (_, 0) => None,
+                        (missing, rate) => Some(missing.div_ceil(rate)),
+                    }
+                })
+                .try_fold(0, |wait, m| m.map(|m| wait.max(m)))
+            else {
+                continue;
+            };
+            if wait + 1 >= time_left {
+                continue;
+            }
+
+            let time_left = time_left - wait - 1;
+            let stock: [u32; 3] =
+                std::array::from_fn(|m| stock[m] + robots[m] * (wait + 1) - self.costs[kind][m]);
+            if kind == Mineral::Geode as usize {
+                self.run(time_left, robots, stock, geodes + time_left);
+            } else {
+                let mut robots = robots;
+                robots[kind] += 1;
+                self.run(time_left, robots, stock, geodes);
+            }
         }
     }
 }
@@ -81,15 +182,28 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input.lines().map(|l| l.into()).collect()
+        self.input = Input::new(input)
+            .lines()
+            .map(Blueprint::parse)
+            .collect::<Result<_, _>>()
+            .unwrap()
     }
 
     fn solve_part1(&self) -> String {
-        "".to_string()
+        self.input
+            .iter()
+            .map(|b| b.idx as u32 * b.max_geodes(24))
+            .sum::<u32>()
+            .to_string()
     }
 
     fn solve_part2(&self) -> String {
-        "".to_string()
+        self.input
+            .iter()
+            .take(3)
+            .map(|b| b.max_geodes(32))
+            .product::<u32>()
+            .to_string()
     }
 }
 
@@ -108,7 +222,7 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
         let mut solver = Solution::default();
         solver.with_input(get_example_input());
         let solution = solver.solve_part1();
-        assert_eq!(solution, "");
+        assert_eq!(solution, "33");
     }
 
     #[test]
@@ -116,7 +230,16 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
         let mut solver = Solution::default();
         solver.with_input(get_example_input());
         let solution = solver.solve_part2();
-        assert_eq!(solution, "");
+        assert_eq!(solution, "3472");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let line = get_example_input().lines().next().unwrap();
+        let error =
+            Blueprint::parse(Input::new(&line.replace("7 obsidian", "7 geode"))).unwrap_err();
+        assert_eq!(error.message, "robots can't cost geodes");
+        assert_eq!(error.column, line.find("obsidian.").unwrap() + 1);
     }
 
     #[test]
@@ -124,7 +247,7 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
         let mut solver = Solution::default();
         solver.with_input(solver.get_input());
         let solution = solver.solve_part1();
-        assert_eq!(solution, "");
+        assert_eq!(solution, "1306");
     }
 
     #[test]
@@ -132,7 +255,7 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
         let mut solver = Solution::default();
         solver.with_input(solver.get_input());
         let solution = solver.solve_part2();
-        assert_eq!(solution, "");
+        assert_eq!(solution, "37604");
     }
 }
 
@@ -1,4 +1,66 @@
-use crate::solver::Solver;
+use crate::{
+    params::{assign, ParamError},
+    parse::parse_lines,
+    solver::Solver,
+};
+
+/// A list of the ids `0..n` split into blocks of about `√n`, so finding an
+/// id's position, removing it and inserting it each cost `O(√n)`.
+#[derive(Debug)]
+struct BlockList {
+    blocks: Vec<Vec<usize>>,
+    block_of: Vec<usize>,
+    block_size: usize,
+}
+
+impl BlockList {
+    fn new(len: usize) -> Self {
+        let mut list = Self {
+            blocks: vec![(0..len).collect()],
+            block_of: vec![0; len],
+            block_size: (len as f64).sqrt().ceil().max(1.0) as usize,
+        };
+        list.rebuild();
+        list
+    }
+
+    fn rebuild(&mut self) {
+        let ids = self.iter().collect::<Vec<usize>>();
+        self.blocks = ids.chunks(self.block_size).map(|c| c.to_vec()).collect();
+        for (b, block) in self.blocks.iter().enumerate() {
+            for id in block {
+                self.block_of[*id] = b;
+            }
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().flatten().copied()
+    }
+
+    /// Takes `id` out of the list, returning where it was.
+    fn remove(&mut self, id: usize) -> usize {
+        let b = self.block_of[id];
+        let offset = self.blocks[b].iter().position(|i| *i == id).unwrap();
+        self.blocks[b].remove(offset);
+        self.blocks[..b].iter().map(Vec::len).sum::<usize>() + offset
+    }
+
+    fn insert(&mut self, mut index: usize, id: usize) {
+        let last = self.blocks.len() - 1;
+        for (b, block) in self.blocks.iter_mut().enumerate() {
+            if index <= block.len() || b == last {
+                block.insert(index, id);
+                self.block_of[id] = b;
+                if block.len() > 2 * self.block_size {
+                    self.rebuild();
+                }
+                return;
+            }
+            index -= block.len();
+        }
+    }
+}
 
 #[derive(Default, Debug)]
 struct EncryptedFile {
@@ -6,51 +68,51 @@ struct EncryptedFile {
 }
 
 impl EncryptedFile {
-    fn decrypt(&self, mix: u8, decryption_key: i64) -> i64 {
-        let mut output = self
+    fn decrypt(&self, mix: usize, decryption_key: i64) -> i64 {
+        let values = self
             .content
             .iter()
-            .enumerate()
-            .map(|(i, n)| (*n * decryption_key, i as i64))
-            .collect::<Vec<(i64, i64)>>();
-
-        let len = output.len() as i64;
-
-        for _ in 0..mix {
-            let mut cur = 0;
-            while cur < len {
-                for idx in 0..len as usize {
-                    let (n, i) = output[idx];
-                    if cur != i {
-                        continue;
-                    } else {
-                        let mut new_idx = (idx as i64 + n) % (len - 1);
-                        if new_idx <= 0 && n < 0 {
-                            new_idx += len - 1;
-                        }
+            .map(|n| n * decryption_key)
+            .collect::<Vec<i64>>();
+        let len = values.len();
+        let mut list = BlockList::new(len);
 
-                        output.remove(idx);
-                        output.insert(new_idx as usize, (n, i));
-                        cur += 1;
-                    }
+        // moving past the end wraps round a list that's one shorter, as the
+        // number being moved is no longer in it
+        if len > 1 {
+            for _ in 0..mix {
+                for (id, n) in values.iter().enumerate() {
+                    let from = list.remove(id);
+                    let to = (from as i64 + n).rem_euclid(len as i64 - 1);
+                    list.insert(to as usize, id);
                 }
             }
         }
 
-        let zero = output.iter().position(|c| c.0 == 0).unwrap();
-
-        let len = output.len();
-        let a = output.get((zero + 1000) % len).unwrap();
-        let b = output.get((zero + 2000) % len).unwrap();
-        let c = output.get((zero + 3000) % len).unwrap();
-
-        a.0 + b.0 + c.0
+        let output = list.iter().map(|id| values[id]).collect::<Vec<i64>>();
+        let zero = output.iter().position(|n| *n == 0).unwrap();
+        [1000, 2000, 3000]
+            .iter()
+            .map(|offset| output[(zero + offset) % len])
+            .sum()
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Solution {
     input: EncryptedFile,
+    key: i64,
+    rounds: usize,
+}
+
+impl Default for Solution {
+    fn default() -> Self {
+        Self {
+            input: EncryptedFile::default(),
+            key: 811589153,
+            rounds: 10,
+        }
+    }
 }
 
 impl Solver for Solution {
@@ -60,7 +122,7 @@ impl Solver for Solution {
 
     fn with_input(&mut self, input: &str) {
         self.input = EncryptedFile {
-            content: input.lines().map(|l| l.parse().unwrap()).collect(),
+            content: parse_lines(input).unwrap(),
         }
     }
 
@@ -69,7 +131,15 @@ impl Solver for Solution {
     }
 
     fn solve_part2(&self) -> String {
-        self.input.decrypt(10, 811589153).to_string()
+        self.input.decrypt(self.rounds, self.key).to_string()
+    }
+
+    fn set_param(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        match name {
+            "key" => assign(&mut self.key, name, value),
+            "rounds" => assign(&mut self.rounds, name, value),
+            _ => Err(ParamError::unknown(name)),
+        }
     }
 }
 
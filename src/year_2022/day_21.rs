@@ -1,26 +1,17 @@
-use std::{collections::HashMap, ops::Div, str::FromStr};
+use std::collections::HashMap;
 
-use crate::solver::Solver;
+use crate::{
+    expr::{BinOp, ExprTree, NodeId},
+    parse::{complete, literal, pattern, signed, terminated, word, Input, ParseError, Parser},
+    solver::Solver,
+};
 
-#[derive(Debug, Clone)]
-enum Operation {
-    Add,
-    Sub,
-    Mul,
-    Div,
-}
+const ROOT: &str = "root";
+const HUMAN: &str = "humn";
 
-impl FromStr for Operation {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "+" => Ok(Operation::Add),
-            "-" => Ok(Operation::Sub),
-            "*" => Ok(Operation::Mul),
-            "/" => Ok(Operation::Div),
-            _ => Err(format!("bad operation value {s}")),
-        }
-    }
+fn parse_op(input: Input) -> Result<BinOp, ParseError> {
+    BinOp::from_symbol(input.as_str())
+        .ok_or_else(|| input.error(format!("bad operation value {}", input.as_str())))
 }
 
 #[derive(Debug, Clone)]
@@ -28,7 +19,7 @@ enum MonkeyOp {
     Value(i64),
     Operation {
         left: String,
-        op: Operation,
+        op: BinOp,
         right: String,
     },
 }
@@ -39,22 +30,22 @@ struct Monkey {
     op: MonkeyOp,
 }
 
-impl FromStr for Monkey {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (id, rest) = s.split_once(": ").unwrap();
-        let rest: Vec<&str> = rest.split(' ').collect();
+impl Monkey {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let (id, rest) = terminated(word(), literal(": ")).parse(input)?;
+        let op = if let Ok(value) = complete(signed(), rest) {
+            MonkeyOp::Value(value)
+        } else {
+            let [left, op, right] = complete(pattern("{} {} {}"), rest)?;
+            MonkeyOp::Operation {
+                left: left.as_str().to_string(),
+                op: parse_op(op)?,
+                right: right.as_str().to_string(),
+            }
+        };
         Ok(Monkey {
             id: id.to_string(),
-            op: if rest.len() == 1 {
-                MonkeyOp::Value(rest[0].parse().unwrap())
-            } else {
-                MonkeyOp::Operation {
-                    left: rest[0].to_string(),
-                    op: rest[1].parse().unwrap(),
-                    right: rest[2].to_string(),
-                }
-            },
+            op,
         })
     }
 }
@@ -64,43 +55,34 @@ pub struct Solution {
     input: HashMap<String, Monkey>,
 }
 
-fn resolve_monkey(monkeys: &HashMap<String, Monkey>, monkey_id: &str) -> i64 {
-    let monkey_op = monkeys.get(monkey_id).unwrap().op.clone();
-    match &monkey_op {
-        MonkeyOp::Value(value) => *value,
-        MonkeyOp::Operation { left, op, right } => {
-            let left_val = resolve_monkey(monkeys, left);
-            let right_val = resolve_monkey(monkeys, right);
+impl Solution {
+    /// Adds the expression yelled by `monkey_id` to `tree`. With
+    /// `human_unknown` the human's own number is left as the unknown.
+    fn build(&self, tree: &mut ExprTree, monkey_id: &str, human_unknown: bool) -> NodeId {
+        if human_unknown && monkey_id == HUMAN {
+            return tree.unknown();
+        }
 
-            match op {
-                Operation::Add => left_val.saturating_add(right_val),
-                Operation::Sub => left_val.saturating_sub(right_val),
-                Operation::Mul => left_val.saturating_mul(right_val),
-                Operation::Div => (left_val as f64).div(right_val as f64).round() as i64,
+        match &self.input.get(monkey_id).unwrap().op {
+            MonkeyOp::Value(value) => tree.value(*value),
+            MonkeyOp::Operation { left, op, right } => {
+                let left = self.build(tree, left, human_unknown);
+                let right = self.build(tree, right, human_unknown);
+                tree.binary(*op, left, right)
             }
         }
     }
-}
 
-fn contains_monkey(monkeys: &HashMap<String, Monkey>, monkey_id: &str, contains_id: &str) -> bool {
-    if monkey_id == contains_id {
-        return true;
-    }
-    if let Some(monkey) = monkeys.get(monkey_id) {
-        let op = &monkey.op;
-        match op {
-            MonkeyOp::Operation { left, op: _, right } => {
-                if left == contains_id || right == contains_id {
-                    return true;
-                }
-                let left_val = contains_monkey(monkeys, left, contains_id);
-                let right_val = contains_monkey(monkeys, right, contains_id);
-                left_val || right_val
-            }
-            _ => false,
-        }
-    } else {
-        false
+    /// The tree for part 2, along with the two sides root compares.
+    fn root_equation(&self) -> (ExprTree, NodeId, NodeId) {
+        let MonkeyOp::Operation { left, right, .. } = &self.input.get(ROOT).unwrap().op else {
+            panic!("root must compare two monkeys");
+        };
+
+        let mut tree = ExprTree::new(HUMAN);
+        let lhs = self.build(&mut tree, left, true);
+        let rhs = self.build(&mut tree, right, true);
+        (tree, lhs, rhs)
     }
 }
 
@@ -110,62 +92,24 @@ impl Solver for Solution {
     }
 
     fn with_input(&mut self, input: &str) {
-        self.input = input
+        self.input = Input::new(input)
             .lines()
             .map(|l| {
-                let monkey: Monkey = l.parse().unwrap();
+                let monkey = Monkey::parse(l).unwrap();
                 (monkey.id.clone(), monkey)
             })
             .collect();
     }
 
     fn solve_part1(&self) -> String {
-        resolve_monkey(&self.input, "root").to_string()
+        let mut tree = ExprTree::new(HUMAN);
+        let root = self.build(&mut tree, ROOT, false);
+        tree.evaluate(root).unwrap().to_string()
     }
 
     fn solve_part2(&self) -> String {
-        let root = self.input.get("root").unwrap();
-        if let MonkeyOp::Operation { left, op: _, right } = &root.op {
-            let (mut contains_humn, other_side) = if contains_monkey(&self.input, left, "humn") {
-                (left, right)
-            } else {
-                (right, left)
-            };
-
-            let mut other_val = resolve_monkey(&self.input, other_side);
-            loop {
-                let humn_side = self.input.get(contains_humn).unwrap();
-                match &humn_side.op {
-                    MonkeyOp::Value(_) => {
-                        assert_eq!(humn_side.id, "humn".to_string());
-                        return other_val.to_string();
-                    }
-                    MonkeyOp::Operation { left, op, right } => {
-                        contains_humn = if contains_monkey(&self.input, left, "humn") {
-                            let right_val = resolve_monkey(&self.input, right);
-                            match op {
-                                Operation::Add => other_val -= right_val,
-                                Operation::Sub => other_val += right_val,
-                                Operation::Div => other_val *= right_val,
-                                Operation::Mul => other_val /= right_val,
-                            }
-                            left
-                        } else {
-                            let left_val = resolve_monkey(&self.input, left);
-                            match op {
-                                Operation::Add => other_val -= left_val,
-                                Operation::Sub => other_val = -(other_val - left_val),
-                                Operation::Div => other_val = left_val / other_val,
-                                Operation::Mul => other_val /= left_val,
-                            }
-                            right
-                        };
-                    }
-                }
-            }
-        } else {
-            "wrong root".to_string()
-        }
+        let (tree, lhs, rhs) = self.root_equation();
+        tree.equation(lhs, rhs).solve().unwrap().to_string()
     }
 }
 
@@ -208,6 +152,17 @@ hmdt: 32"
         assert_eq!(solution, "301");
     }
 
+    #[test]
+    fn test_reduced_equation() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let (tree, lhs, rhs) = solver.root_equation();
+        assert_eq!(
+            tree.equation(lhs, rhs).to_string(),
+            "(4 + (2 * (humn - 3))) / 4 = 150"
+        );
+    }
+
     #[test]
     fn test_solution_part1() {
         let mut solver = Solution::default();
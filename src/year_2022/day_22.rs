@@ -0,0 +1,606 @@
+use crate::{
+    parse::{complete, many, one_of, unsigned, Input, ParseError, ParseResult, Parser},
+    solver::Solver,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Void,
+    Open,
+    Wall,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Facing {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
+impl Facing {
+    const ALL: [Facing; 4] = [Facing::Right, Facing::Down, Facing::Left, Facing::Up];
+
+    fn turn(&self, quarters: usize) -> Facing {
+        Facing::ALL[(*self as usize + quarters) % 4]
+    }
+
+    /// `(row, column)` offset of one step.
+    fn delta(&self) -> (i32, i32) {
+        match self {
+            Facing::Right => (0, 1),
+            Facing::Down => (1, 0),
+            Facing::Left => (0, -1),
+            Facing::Up => (-1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Forward(u32),
+    Left,
+    Right,
+}
+
+fn parse_step(input: Input) -> ParseResult<Step> {
+    if let Ok((n, rest)) = unsigned().parse(input) {
+        return Ok((Step::Forward(n), rest));
+    }
+    one_of(&["L", "R"])
+        .map(|turn| if turn == "L" { Step::Left } else { Step::Right })
+        .parse(input)
+}
+
+type Pos = (i32, i32);
+
+#[derive(Debug, Default)]
+struct Board {
+    tiles: Vec<Vec<Tile>>,
+}
+
+impl Board {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let tiles = input
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .enumerate()
+                    .map(|(i, c)| match c {
+                        ' ' => Ok(Tile::Void),
+                        '.' => Ok(Tile::Open),
+                        '#' => Ok(Tile::Wall),
+                        c => Err(line.advance(i).error(format!("unknown tile `{c}`"))),
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { tiles })
+    }
+
+    fn tile(&self, (row, col): Pos) -> Tile {
+        if row < 0 || col < 0 {
+            return Tile::Void;
+        }
+        self.tiles
+            .get(row as usize)
+            .and_then(|r| r.get(col as usize))
+            .copied()
+            .unwrap_or(Tile::Void)
+    }
+
+    fn start(&self) -> Pos {
+        let col = self.tiles[0].iter().position(|t| *t == Tile::Open).unwrap();
+        (0, col as i32)
+    }
+
+    /// Follows `path`, calling `wrap` whenever a step would leave the map.
+    fn walk(
+        &self,
+        mut pos: Pos,
+        mut facing: Facing,
+        path: &[Step],
+        wrap: impl Fn(Pos, Facing) -> (Pos, Facing),
+    ) -> (Pos, Facing) {
+        for step in path {
+            match step {
+                Step::Left => facing = facing.turn(3),
+                Step::Right => facing = facing.turn(1),
+                Step::Forward(n) => {
+                    for _ in 0..*n {
+                        let (dr, dc) = facing.delta();
+                        let next = (pos.0 + dr, pos.1 + dc);
+                        let (next, next_facing) = if self.tile(next) == Tile::Void {
+                            wrap(pos, facing)
+                        } else {
+                            (next, facing)
+                        };
+                        if self.tile(next) == Tile::Wall {
+                            break;
+                        }
+                        (pos, facing) = (next, next_facing);
+                    }
+                }
+            }
+        }
+        (pos, facing)
+    }
+
+    /// Wraps around to the far side of the current row or column.
+    fn flat_wrap(&self, pos: Pos, facing: Facing) -> (Pos, Facing) {
+        let (dr, dc) = facing.delta();
+        let mut pos = pos;
+        while self.tile((pos.0 - dr, pos.1 - dc)) != Tile::Void {
+            pos = (pos.0 - dr, pos.1 - dc);
+        }
+        (pos, facing)
+    }
+}
+
+type Vec3 = [i32; 3];
+
+fn neg(v: Vec3) -> Vec3 {
+    v.map(|x| -x)
+}
+
+/// Where a face of the net ends up once folded: the directions its columns
+/// and rows run along, and the direction it faces out of the cube.
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    origin: Pos,
+    right: Vec3,
+    down: Vec3,
+    normal: Vec3,
+}
+
+impl Face {
+    fn direction(&self, facing: Facing) -> Vec3 {
+        match facing {
+            Facing::Right => self.right,
+            Facing::Down => self.down,
+            Facing::Left => neg(self.right),
+            Facing::Up => neg(self.down),
+        }
+    }
+
+    /// The face across the edge in direction `facing`, rolled over that edge.
+    fn roll(&self, facing: Facing, origin: Pos) -> Face {
+        let mut face = Face {
+            origin,
+            normal: self.direction(facing),
+            ..*self
+        };
+        match facing {
+            Facing::Right => face.right = neg(self.normal),
+            Facing::Left => face.right = self.normal,
+            Facing::Down => face.down = neg(self.normal),
+            Facing::Up => face.down = self.normal,
+        }
+        face
+    }
+}
+
+/// A net folded into a cube, found by rolling a cube across the net, so it
+/// works for any net and face size.
+#[derive(Debug)]
+struct Cube {
+    size: i32,
+    faces: Vec<Face>,
+}
+
+impl Cube {
+    fn fold(board: &Board) -> Option<Cube> {
+        let area = board
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|t| **t != Tile::Void)
+            .count();
+        let size = ((area / 6) as f64).sqrt() as i32;
+        if size == 0 || (size * size * 6) as usize != area {
+            return None;
+        }
+
+        let start = board.start();
+        let mut faces = vec![Face {
+            origin: (0, start.1 / size * size),
+            right: [1, 0, 0],
+            down: [0, 1, 0],
+            normal: [0, 0, -1],
+        }];
+        let mut i = 0;
+        while let Some(face) = faces.get(i).copied() {
+            for facing in Facing::ALL {
+                let (dr, dc) = facing.delta();
+                let origin = (face.origin.0 + dr * size, face.origin.1 + dc * size);
+                if board.tile(origin) != Tile::Void && faces.iter().all(|f| f.origin != origin) {
+                    faces.push(face.roll(facing, origin));
+                }
+            }
+            i += 1;
+        }
+
+        let distinct = faces
+            .iter()
+            .all(|a| faces.iter().filter(|b| a.normal == b.normal).count() == 1);
+        (faces.len() == 6 && distinct).then_some(Cube { size, faces })
+    }
+
+    fn face_with(&self, pred: impl Fn(&Face) -> bool) -> &Face {
+        self.faces.iter().find(|f| pred(f)).unwrap()
+    }
+
+    /// Steps over the edge of the face holding `pos` onto the next face.
+    fn wrap(&self, pos: Pos, facing: Facing) -> (Pos, Facing) {
+        let size = self.size;
+        let from = self.face_with(|f| f.origin == (pos.0 / size * size, pos.1 / size * size));
+        let to = self.face_with(|f| f.normal == from.direction(facing));
+        let to_facing = *Facing::ALL
+            .iter()
+            .find(|f| to.direction(**f) == neg(from.normal))
+            .unwrap();
+
+        // how far along the shared edge we are, and the axis it runs along
+        let (along, axis) = match facing {
+            Facing::Right | Facing::Left => (pos.0 - from.origin.0, from.down),
+            Facing::Down | Facing::Up => (pos.1 - from.origin.1, from.right),
+        };
+        let edge = |dir: Vec3| if dir == axis { along } else { size - 1 - along };
+        let (row, col) = match to_facing {
+            Facing::Right => (edge(to.down), 0),
+            Facing::Left => (edge(to.down), size - 1),
+            Facing::Down => (0, edge(to.right)),
+            Facing::Up => (size - 1, edge(to.right)),
+        };
+
+        ((to.origin.0 + row, to.origin.1 + col), to_facing)
+    }
+}
+
+fn password(((row, col), facing): (Pos, Facing)) -> String {
+    (1000 * (row + 1) + 4 * (col + 1) + facing as i32).to_string()
+}
+
+#[derive(Default, Debug)]
+pub struct Solution {
+    board: Board,
+    path: Vec<Step>,
+}
+
+impl Solver for Solution {
+    fn get_input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn with_input(&mut self, input: &str) {
+        let mut blocks = Input::new(input).blocks();
+        let (map, path) = (blocks.next().unwrap(), blocks.next().unwrap());
+        self.board = Board::parse(map).unwrap();
+        self.path = complete(many(parse_step), path.trim()).unwrap();
+    }
+
+    fn solve_part1(&self) -> String {
+        password(self.board.walk(
+            self.board.start(),
+            Facing::Right,
+            &self.path,
+            |pos, facing| self.board.flat_wrap(pos, facing),
+        ))
+    }
+
+    fn solve_part2(&self) -> String {
+        let cube = Cube::fold(&self.board).expect("map doesn't fold into a cube");
+        password(self.board.walk(
+            self.board.start(),
+            Facing::Right,
+            &self.path,
+            |pos, facing| cube.wrap(pos, facing),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_example_input<'a>() -> &'a str {
+        "        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5"
+    }
+
+    #[test]
+    fn test_solution_example1() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "6032");
+    }
+
+    #[test]
+    fn test_solution_example2() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "5031");
+    }
+
+    #[test]
+    fn test_solution_part1() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "1588");
+    }
+
+    #[test]
+    fn test_solution_part2() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "32401");
+    }
+
+    #[test]
+    fn test_every_net_folds() {
+        const NETS: [&str; 12] = [
+            "#...\n####\n#...",
+            "#...\n####\n.#..",
+            "#...\n####\n..#.",
+            "#...\n####\n...#",
+            ".#..\n####\n.#..",
+            ".#..\n####\n..#.",
+            "##..\n.###\n.#..",
+            "##..\n.###\n..#.",
+            "##..\n.###\n...#",
+            "##..\n.##.\n..##",
+            "###..\n..###",
+            // the shape of the real inputs
+            ".##\n.#.\n##.\n#..",
+        ];
+        let size = 5;
+
+        for net in NETS {
+            // blow each face up into an open square
+            let map = net
+                .lines()
+                .flat_map(|line| {
+                    let row = line
+                        .chars()
+                        .map(|c| if c == '#' { "." } else { " " }.repeat(size))
+                        .collect::<String>();
+                    std::iter::repeat_n(row, size)
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            let board = Board::parse(Input::new(&map)).unwrap();
+            let cube = Cube::fold(&board).unwrap();
+
+            // going all the way round the cube ends up back at the start
+            let lap = [Step::Forward(4 * size as u32)];
+            for (row, tiles) in board.tiles.iter().enumerate() {
+                for col in (0..tiles.len()).filter(|c| tiles[*c] == Tile::Open) {
+                    for facing in Facing::ALL {
+                        let start = (row as i32, col as i32);
+                        let end = board.walk(start, facing, &lap, |p, f| cube.wrap(p, f));
+                        assert_eq!(end, (start, facing), "net:\n{net}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+const INPUT: &str = "                                                  .......................#.....#...............#...................................#...............#..
+                                                  .....#.............................#...........................................#..#.............#...
+                                                  .......................#......#.....#..........#...................#................................
+                                                  .........#............#................##.....................................#..............#....#.
+                                                  .............#...............#.....#..........#.........................#..#..#.....................
+                                                  .....................#........#...#.......................#......#................#.............#...
+                                                  ....##........#.............#.#....#................#....#...........................#...#....#.....
+                                                  #..............##................#....#...................................#..#......................
+                                                  ##.............#................................#.......#...........................................
+                                                  ...#...........#..#.....#......................................#..#...#.................#...........
+                                                  .#.#....................#.........#...................#..###............#........#..................
+                                                  ..........#..................................................##.................................##..
+                                                  ....................#...#.....#..............#..........#..............................#............
+                                                  ..........................#..............#..........#......#..............#..................#......
+                                                  ....#.........#..............#..#.....................#.....##.............#.....#..................
+                                                  ..#.#............................................................#....................#.............
+                                                  .#....................................#.##..............#...........................................
+                                                  ......................#.#............................#...#........#..#...#........##.......#.......#
+                                                  ....................................................................#....#..........................
+                                                  ...........................................................#.................#.......#..............
+                                                  ...##...................................................#....#.........#........#...................
+                                                  ..#...................#....#.............#.......#................#....................#.....#......
+                                                  ..........#..#..........#...............#..#.............#............#....#........................
+                                                  ....................................#...##..........................................................
+                                                  ..............#..............................#...........................#..........#...............
+                                                  .....##.#......#...........#...#.........#................................#.........................
+                                                  ............#...#.......#.......................#.............................#..................##.
+                                                  ...............#...............................##..............#..............#............#........
+                                                  ...................#....................................................#..............#..........##
+                                                  .#...........#.....................#...............................#..................#.........#...
+                                                  ...#...................................#....##........###...................#.....#...............#.
+                                                  .................#................#....#......................#.....#..#............................
+                                                  ......#............................#.........#...#..........#.....................#..#.#...........#
+                                                  ...#................#...................#................#.......##...................#..#........#.
+                                                  ..#...#.......#..........#..#.......................##........#.......#.............................
+                                                  ...................#.......................#....#.........#.....#........#........#.............#...
+                                                  .....................................#..............#...............................................
+                                                  ................#...................#.......#.......#.......#........#..##..........................
+                                                  ......#....................#..................................#...................#.................
+                                                  .......#....#......................................#.....#..#........##.......#......##....#......#.
+                                                  ..............#...................##...................................#.................##......#..
+                                                  .#...........#.#.........................................#...#................#.....................
+                                                  ...#...........................#..............................#.......................#.............
+                                                  ...........................................#.......#...#.........##..........#.#....................
+                                                  ................#.........#.................................#...#......#............................
+                                                  ..#.................#....................#......#.....#......#..........................#..#....#...
+                                                  ...#.#................##.#............................#......#.................#......#.............
+                                                  .......................................#..........#...............#..................#.............#
+                                                  .#.#.................#..#.....#.............#.......................#.....#..........#..............
+                                                  ..#..........#...........#...........#.....#............#....#..#........#......#..#.....#.#...#..#.
+                                                  .........#....#............#...#...#.........#....
+                                                  ...............#.......#........#........#........
+                                                  .......#..............#.......................#...
+                                                  .......................##.#...##..................
+                                                  ...#..#.................................#.......#.
+                                                  .......#.#...#...............#..............#...##
+                                                  ....................................#.............
+                                                  .........................#.#........#.......##....
+                                                  .....##....................#......................
+                                                  ....#..#........#.......#......#..........#.....#.
+                                                  .............#..............................#.....
+                                                  ................#.##.......#.....#...#...###...#..
+                                                  ....................#.................#...#.......
+                                                  .............#..................................#.
+                                                  ..#..#......#...........#.........................
+                                                  .#............................................#...
+                                                  ..................................##..........#.#.
+                                                  ...........#...........#........#.................
+                                                  .........#........#................#...........#..
+                                                  ...#.....................#..#.....................
+                                                  ......#..#.............................#..........
+                                                  ..#.....#...................#.........#...........
+                                                  ...........#.......#..................#.....#.....
+                                                  ....................#............#.........#......
+                                                  .....#...................#........#...............
+                                                  ................#......#.........................#
+                                                  #................#.........#.................#..#.
+                                                  .#........................#.........#.............
+                                                  ......................#........................#..
+                                                  ...#...............#........#.....................
+                                                  .........#............#....................#.#....
+                                                  ........#..#...........................##.........
+                                                  ...............#...........#.......#.....#........
+                                                  #.......#......#.##...............#..#............
+                                                  .......#.......##........................#........
+                                                  ..............#...............................#...
+                                                  .................#....................#.#..#......
+                                                  ..#...#...........................................
+                                                  ....#.......#...#....#....#.................#.#...
+                                                  ..........#........#..............................
+                                                  ............#...............#.....#...............
+                                                  .#.#..#..#....#.....#...#...#.....................
+                                                  .....................#..........#.................
+                                                  ..............................#..#..............#.
+                                                  .................#.........................#...#..
+                                                  ....#....#.....#.....#...#......#.................
+                                                  ............................#.....#.........#.....
+                                                  ...#......#......#................#...............
+                                                  .................................#.........#......
+                                                  .#...........#.........#.......#..................
+..........................#....#.....#.........#....#......#...........##.#.........................
+....................#.........................#....#...........#.....................#..............
+#.....................................#.............#.........#...............................#.###.
+.......................#..................#...#..........#.#....#.......#..................#......#.
+..............................................#........................#....#.......................
+#....#.............................#.........................##.........#................#..........
+.....#.#.................................................#........#......#..........................
+#..........#..............#................#............##.#..................#...#.................
+.........#.........#.......#.....##....#...........#.......................#....#.#....#............
+........................................#...........................................................
+......................#..............#........#...........#............#............................
+.................................#.........##....#...............#.......#........................#.
+.........#..###.....#.#.................#...........................................#...............
+..#............#........................#.........#..........#..........#......#...#......#.........
+#....#................................#..........##................#................................
+..........#............................................................................#.........#..
+.........#..........................#.......................#.....#....#............................
+..........................#.....................#.......#.................#..#......................
+.......#.............#...............#....................#...#......#....................#.........
+....................................#...................................##.........#................
+...............................#..............#......#......#............##............#...........#
+..........#.....#.....#.......#...#.......##.....#........#..#.......#..#..................#........
+#.............................#.#...#...........#...........#........#..#...........................
+.......#.......................................#....#.....##......#...#.......................#.....
+.................#.................#.#.........................................#.##......#..#.#...#.
+................................................#...#..#.........#..............#...........#.......
+............#.#.................................#.........#.........................................
+.................#......#........#.....##.....#.........#...........................................
+.........................##.........#....#......##.........#................................#.......
+..........#...........#.......##...................................#...#.#.....................#....
+#...................#.......................#....#.....................##..##............##.........
+........................#......#...................#.......................#....#..........#........
+#........................#.....................#...................#.................#.............#
+....#...............#.....#..##..#....................#...........#.........#...............#.#.....
+.................#................................#....#........#....#........................#.....
+#........#.......#...............#.....................#...........................................#
+............#..#.#.....#.....................#..#.........................#.....#.#.................
+...............#...............#..................#.................................................
+..........#.........#..#...........................#.....................................#..........
+.....................#.............#....#..........#..........................#......#....#.........
+..#...#..................#....................#........#.........................................#..
+....................#.........#.......#........#.............#.#.....................#.#............
+..........................#..........#.#............#.........#..#..................#.......#....##.
+...................#..............#..............#..........#.......................................
+....#..#.............................###...#.....#.......#.........#.#.#..#.............#..#........
+.......#.......................#......#........#.....#.......#...........#...##.....................
+............#.......#....................................................#...#.#.......#.#..........
+....#..........................#................##...#......#........#.#......#..........#.....#....
+.....#......................#..##.................#.................................................
+..#.............................##..................................#....#..........................
+...........................##.................#...
+#...........................#.#...................
+..................................................
+..........................#.......................
+........#.............#.............#............#
+........#.....#.#.................#..............#
+.#...#....#.##.........#...................#.....#
+......#..............#............#...............
+.......#............#..#..........#..............#
+..........................##.#..........#....#..#.
+.......#.#............................#.#.........
+......#....#.....#..#.................###......#..
+#...#......#............................#.........
+...............#...............#.................#
+.................#........#.......................
+#...............#.#..........#....#...............
+.....#..................#.........................
+......................#......................#....
+.......................##...........#..#..........
+...........#...#.#......#.....#...................
+.......#........#.......#..#..#........#..........
+.........#..#...........#........#.......#........
+.#........#....................##...#.....#.....#.
+......................#........#..#......#........
+...............##..................#..............
+.........#....#...................................
+...............#...#...............#....#..#......
+#..................................#..............
+.....#......................#..#.......#.....#....
+..#........#.....#..#............................#
+....##.......#...........#.........#......#.#...#.
+..................................#.......#.......
+......#......#..#...................###...........
+.....#..#.........#.......#.....#.................
+.#...............##.....#.............#...........
+..................................#...............
+.........#.......##..........................#....
+..............#.#.......#.#.......#...............
+............#.....................................
+.....##.........................#.....#....#......
+....#.#...........................................
+.........#....................#..#...........#....
+.................................................#
+.......#....#............#........................
+.........................#........................
+.#.........................#...........#......#...
+............#.#.........#.........................
+....#.................#................#..........
+....#..........................................#.#
+..........#..........#...#........................
+
+8L13R36R19R15R19R37R6L35R22L44L46L39R25R24L14R34R49R13R8L43L36L43L24R10R30L15L29L30L25R38L7R48L31L1L2L8L39R9R36R6R34R18L3R1L34L4L1L44R42L27L13L35R35L40R9R34R41R22R26L8L15L45L5R36L15L28L6L21R40L20R22L24L49R19R31L7R1R27R12L42L37R35R17L15R13L43R4L2R25R37L10L27L31L24R5R12L37R27R31R26L8R26R29R1R6L47R14L24R13R13L49L13R15L41R28L49L18L8L39R6R24L42L48R21R47R22R7L43L3R25L10R46R21L43L29R15L38L22R46R7R29L29R2L20R40L33R27L18R15L9L3R11R38L47R20R40R17R46L31R2L45R12L22L23L17L40L49L12L14R9L47R9L9L40R31L28R29L41R18L14L33R10R3R14L47L35L4R8L11L43R43R42R29L38L5R33R48L30R9L3L30L8R37R41R1L38L14R9R13R20R11R4L39L22L8L13R25L7R42L2R33R38R3L15L20R1R41L26L27L23L28R30R17R34R39L44R49L38R24R15R39R22R22L34L18L19L28L45R4R17R25L24L10L1L30R2L42L42R24L10L31L19R47L29R49L41R45L2L28R16R9L49L40R15L3R32L32L41R45L24R20L39L31R6R40L34R2L6L10R27L40L46L27L45R16R41R42R43R24R31R20L12L25R44R23R36R18R20L26R2R2R29R36L31L7R31L30R19L29L6L29R3R45R31R43R5R44R1L22R44L21R17R12R44L44R13L1R45R2L7L41L35L36R37R19L42L31L35L18L48L42L26L33L49L25R23L2R11R10R29R7R29L20R43R13R21R47L35R3L37L35R26R3L17L6R14R7L14L18R24L50R1R42R22R32L48R14R15R46R6R40R30R10L16R20R5R13L40L15L3L7R38L50R49R48L35R27L22R38R11R13R45L33R38L32R5R13R10R43R28L10R50L38L47R46R35R3L5R50L14R11L48R44L8R3L14L7R18R7R6L20R42R27R48R5R14L25R48L31L23R42R21R1R25R19L7R28R29L20R7L28R42L31R42R8R16R3R26L50L23L29R6L49L33L32L4L37L27L22L28L30R26L35R14R43L46L21R30L1L2R26L19L44L31R34R19R42R6L21L16R46R8R7L13L45L10R35R4L43L50L45R20R6L43R16R2R38L8R34R49L25L15R37R32R24L36R49R26L26L7R25R17R9R29R42L40R16R25L21L25L46R47R42R39R2R47R18R41R15L33L38L18L21R37R50L6R12R18R31R18R30L15L38R23R24R29R23L34R37R14R26L4L35L1R45R37L45L32R18R17R19L13L31L46R26L5R6R23R24R21L17L12R2L45L24L33L41R22R29R31L47L24L27L44L47L37R24R41L14R25R13R21R9R13R47L47L47L26L7L50L1R6R35R3L50L13R14R49L15L2L18L45L42L44R24L33L15L28L46R30L27L14R41L24L18L3R4R24L49R21R9R36L45R14L23R7R46L37R11L12L35L34R29R50L34R11L28L23R18L12R23L29R1L17R14R1R18R21L28L46R7R26L2L15L14R11R31L4R49R33R45L1L22L32L38L32L44L49R20R43R42L44L49R50L3L44R33R33R48R11L24L19L45R11L20L32R47R17L21L23R9R48R10R30R19L48L16L27R21L6R8L5R28L47R28L16R9L50R28R7R14R30L42L6L9L19L48R20L34R5L20R47R6L13R38L17L34R37R25L29R32L8R38L7R21R14R9R47R3L37L22L46L37R14L31L32R5L34L3R47L27R50L10R27R49L37R24R48R30R20L23R49R46L2L41L28L10R39R1R20L41L6R26R20R33L39L34R35R38R26R38L7R25L7L21L9L3L15R43R46L30R45L49R18R2R15L6L47L15L37L41L37L29L49L43L32L25L48L23L25R28L16L20R2R45R44R12R39L40R6R2R42R48L27L26L23R13L37R19R12R42R42R4R14L45L43L48R21R48R39L24R43L38L34L41L45R39L28L48L16R18R39R8R45L17L47L3R10L15R3R47R35R4R19L31L34R36R34R9R28R4L25L29L6R41R13L17L26L49R40R41L37L22L49L39L33L15R48R44L36L14L8R29R40R41L32R38L23L21L23L36L29R13R41R27R32R38R12L6L49R5L6R19R19R18L7L42R21R16R28L1L50R28L38L41R46R24R39L34R46L17L41R24R17L3L21R35R37R20R20R1L13R3R34L14L26L18L31R10R2R49R32L10L19L33R38R15R45L33L18R40L10R30L5L21L8R11R35L30L42L11R24L18R50R24L46L16R28R8L25L37L12L27R37L48L46R13L4L42L2R16L16R24R28R25L6L14L24L26R22R13L33L29R36L49R9L24L24L34L5R38L17L39R14L12R6L16R36L24L35L23R20R12R42R45R32R6R31L18R26R9L7L17L43R5R44R6L22R9R49R41R19L12L46R22R16L27L36R37L47R7L19L34L41R21L20L36R14L12R16R42L16R28R14L9R46R40L20R15R15L33L14R12L10R50L9R48L31L43R24R36R24R44R34L39L15R1L8R19L8L28L4L27R50L16L7R42L16R12R32R45R21L12R44L31L43L49L19L37R23L37L48L17R37R41L16R38R8L34R15L41L38R10R3L48L41L31L25R29R42L22R31R10L2R36R23R11L18R43R1L41R2L9R29L38R21L18R26R8R31R34R39L15L8L1L2R16L34L2L47R9L2L37R25L44R4L26R49R8R26R41L18L4L49R40L35R46L7R24R24L20L37R17R16R23R46L1R48L29L3L29L10L15L9L9L21L5R48L37L33R40L45L42L10R5R33L18R13R39L25R23L47L35R37L24L30L33R49L27L10L45R36R26L42L22R16R7L12L29R18L10R44L1L2R48L26L32L36L31L9L27R43L35R42R2R43R22R30L47R47R44L37L38R2L26L48L25R11L29R49L49R25R17R3R6R21R26R47R16L8R3L21L8R34R11R4R7L24R24L8L13R6R20L19L9L2L12R19R2L15R37L24R31L2R16L5R1R3L9R7R45L12L9L16R46L10R44R16L34R25R32L47R2L1R38R8L46L7L43R32L3R39R18R12R24R45R8L34R11R19R29R21L6L44R5R6R1L43R12R15L6L31R3L39R9R11L41R1L47R38R34L28L46R41R12R27R1L32R17L14L23R18L46L23L13R23L19R48R20L36R50R5L14L29L4L8R3L3R31R44R11L14L8R35L43R49L7R49L45R7L4L20L46L13R46L25L46R1R30R11R20L11L31L21R26R30R33L36L7R39L29L45L30R40L26L27L16L17L34R32L10R21L14R26R4L44R13L13L46L49L32L7L17R6L11L18R19L47L38R13L31R44L5L18L9R40R46L21L3R40R10L10L22L44R39L23R42L7L11L16R50L32L26R4R22R40R7L4L7L49L46L21R7L7R33L44L32R1L39R21R37L2L23L23L26R9L2R48L38R18R45R28L1R30L26L43R10L50L48R30L11R40L12L19L32L16R2L50R14L36L32R4R38L40L28L8L30R3R41L13R38L31R44L6L47L37R29R7L30L22L45L24L38L5R35R34L36L3L15R7L10L24R39R50L46R50R6L37L14L14R5R35L15L8L7R14R34R9R4R36L22R45L24L48L41R50R35L6L32R29L33R19R42R13L19R9L1L10R4L15L15R19R25L2R1R37L23R11L18L21R31R43L39L12R35L6R16L43R29L13R47L20R26L37R18R38L42L27L4R40R13L31R30R10R50R41L14L39R42L3L12L46L40R48R10L29R20L48R37R25R30L48R23R19R36L34L30L27R44R50L47L37R27R47L39L40L21L10R42R49R8L11L5L27L8R10R40L11L17L24L15R1L8L21L49R46R15L36R39R34R4R22L20R45L2R24R46R23R46R7L34L47L4R12R11L1L15L48L43R23R5L40L22R37L40R43R43R36L12L23L50L9L32R21L12L11R47L43L21L46L49R48R45R4L10R38L22L14R48L1R21L26R21L46L27L8R30R12R30R7R27L39R3L9R11L44L30L1R32R5L23R4L35L22L21R50L46L41L47L18L19L44R3L26R20L38R17R1R25L34R41L6R35R50R21R1L24R44R44R4L14L15L23R40L23R27R17L25R33L50L47L33R50R28R11L9L50L41L22L20L37L12L14R49L12L18R49R40R9R13R8R48R26R18R32L30L3R28L22L15L5L1L35R11R36L30R45L26L47L18L48R26L24L27L48R36R21L13R18L6L50R49L41R50L15L14R43R21L42L26R41R36R19L20R32R35L50L40R4L49R44R47L37R16R14L23L26R1L48R4R18L10L25L18L2R29L42R25R22L17L36L24R9L24L33R37R15L43L38L20R49R9R39L38L40R4L11R47L9L16R13L29L50L20L49L50R17L34R9R6L43R35R30L38R33L36L10R41L26L32R1R18L14R10L34R38R35L5R9R38R39R40L27R49L36R30R20L16R12R7L49R36L50R24L42R11L19L26L4L28R41R42R41L34R46R16R3L20R18R20L35R13R26R41R35L36R35L14L25R13L9R20L11L14R43L12L36R10R38L9";
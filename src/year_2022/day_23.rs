@@ -0,0 +1,357 @@
+use std::{collections::hash_map::Entry, fmt::Display};
+
+use crate::{
+    parse::{Input, ParseError},
+    solver::Solver,
+    utils::FxHashMap,
+};
+
+/// `(row, column)`, with rows growing southwards.
+type Pos = (i32, i32);
+
+/// The three rows around an elf, each as bits for the columns to its west,
+/// its own column and the east.
+type Neighbourhood = [u8; 3];
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    const ORDER: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+
+    /// Whether the three tiles on this side of the elf are empty.
+    fn is_clear(&self, [north, middle, south]: Neighbourhood) -> bool {
+        match self {
+            Direction::North => north == 0,
+            Direction::South => south == 0,
+            Direction::West => (north | middle | south) & 0b001 == 0,
+            Direction::East => (north | middle | south) & 0b100 == 0,
+        }
+    }
+
+    fn offset(&self) -> Pos {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+            Direction::East => (0, 1),
+        }
+    }
+}
+
+/// Elves on an unbounded board, kept as 64 column wide words for only the
+/// stretches of rows that hold any, so a neighbourhood takes a few lookups
+/// rather than one per tile.
+#[derive(Debug, Default, Clone)]
+struct Grove {
+    elves: Vec<Pos>,
+    words: FxHashMap<(i32, i32), u64>,
+    round: usize,
+}
+
+impl Grove {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let mut grove = Self::default();
+        for (row, line) in input.lines().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                match c {
+                    '#' => {
+                        grove.insert((row as i32, col as i32));
+                        grove.elves.push((row as i32, col as i32));
+                    }
+                    '.' => {}
+                    c => return Err(line.advance(col).error(format!("unknown tile `{c}`"))),
+                }
+            }
+        }
+        Ok(grove)
+    }
+
+    fn word(&self, row: i32, block: i32) -> u64 {
+        self.words.get(&(row, block)).copied().unwrap_or(0)
+    }
+
+    fn contains(&self, (row, col): Pos) -> bool {
+        self.word(row, col.div_euclid(64)) >> col.rem_euclid(64) & 1 == 1
+    }
+
+    fn insert(&mut self, (row, col): Pos) {
+        *self.words.entry((row, col.div_euclid(64))).or_default() |= 1 << col.rem_euclid(64);
+    }
+
+    fn remove(&mut self, (row, col): Pos) {
+        let key = (row, col.div_euclid(64));
+        if let Entry::Occupied(mut word) = self.words.entry(key) {
+            *word.get_mut() &= !(1 << col.rem_euclid(64));
+            if *word.get() == 0 {
+                word.remove();
+            }
+        }
+    }
+
+    /// The columns `col - 1 ..= col + 1` of `row`, lowest bit first.
+    fn three(&self, row: i32, col: i32) -> u8 {
+        let (block, shift) = ((col - 1).div_euclid(64), (col - 1).rem_euclid(64));
+        let mut bits = self.word(row, block) >> shift;
+        if shift > 61 {
+            bits |= self.word(row, block + 1) << (64 - shift);
+        }
+        (bits & 0b111) as u8
+    }
+
+    fn proposal(&self, (row, col): Pos) -> Option<Pos> {
+        let around = [-1, 0, 1].map(|dr| self.three(row + dr, col));
+        if around[0] | around[1] & 0b101 | around[2] == 0 {
+            return None;
+        }
+
+        (0..4)
+            .map(|i| Direction::ORDER[(self.round + i) % 4])
+            .find(|direction| direction.is_clear(around))
+            .map(|direction| {
+                let (dr, dc) = direction.offset();
+                (row + dr, col + dc)
+            })
+    }
+
+    /// Plays one round, returning how many elves moved.
+    fn step(&mut self) -> usize {
+        // target -> the elf heading there, or `None` once two elves want it
+        let mut proposals: FxHashMap<Pos, Option<usize>> =
+            FxHashMap::with_capacity_and_hasher(self.elves.len(), Default::default());
+        for (i, elf) in self.elves.iter().enumerate() {
+            if let Some(target) = self.proposal(*elf) {
+                match proposals.entry(target) {
+                    Entry::Vacant(e) => {
+                        e.insert(Some(i));
+                    }
+                    Entry::Occupied(mut e) => {
+                        e.insert(None);
+                    }
+                }
+            }
+        }
+
+        let mut moved = 0;
+        for (target, elf) in proposals {
+            if let Some(i) = elf {
+                self.remove(self.elves[i]);
+                self.insert(target);
+                self.elves[i] = target;
+                moved += 1;
+            }
+        }
+
+        self.round += 1;
+        moved
+    }
+
+    /// The top left and bottom right corners of the smallest rectangle
+    /// holding every elf.
+    fn bounds(&self) -> (Pos, Pos) {
+        self.elves.iter().fold(
+            ((i32::MAX, i32::MAX), (i32::MIN, i32::MIN)),
+            |(min, max), (row, col)| {
+                (
+                    (min.0.min(*row), min.1.min(*col)),
+                    (max.0.max(*row), max.1.max(*col)),
+                )
+            },
+        )
+    }
+
+    fn empty_ground(&self) -> usize {
+        let (min, max) = self.bounds();
+        let area = (max.0 - min.0 + 1) as usize * (max.1 - min.1 + 1) as usize;
+        area - self.elves.len()
+    }
+}
+
+impl Display for Grove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (min, max) = self.bounds();
+        for row in min.0..=max.0 {
+            for col in min.1..=max.1 {
+                let tile = if self.contains((row, col)) { '#' } else { '.' };
+                write!(f, "{tile}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Solution {
+    grove: Grove,
+}
+
+impl Solver for Solution {
+    fn get_input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn with_input(&mut self, input: &str) {
+        self.grove = Grove::parse(Input::new(input)).unwrap();
+    }
+
+    fn solve_part1(&self) -> String {
+        let mut grove = self.grove.clone();
+        for _ in 0..10 {
+            grove.step();
+        }
+        grove.empty_ground().to_string()
+    }
+
+    fn solve_part2(&self) -> String {
+        let mut grove = self.grove.clone();
+        while grove.step() > 0 {}
+        grove.round.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_example_input<'a>() -> &'a str {
+        "\
+....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#.."
+    }
+
+    #[test]
+    fn test_solution_example1() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "110");
+    }
+
+    #[test]
+    fn test_solution_example2() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "20");
+    }
+
+    #[test]
+    fn test_solution_part1() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "4081");
+    }
+
+    #[test]
+    fn test_solution_part2() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "1075");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut grove =
+            Grove::parse(Input::new(".....\n..##.\n..#..\n.....\n..##.\n.....")).unwrap();
+        for _ in 0..3 {
+            grove.step();
+        }
+        assert_eq!(
+            grove.to_string(),
+            "..#..\n....#\n#....\n....#\n.....\n..#..\n"
+        );
+    }
+}
+
+const INPUT: &str = "\
+.#.##..##.######.#.#..###.#.##......####..#.#...###.#.#..##..##...###..##
+#......#.#.####.#.##...#...##..#..#.#.#....#####...##...#.###.###.###.##.
+#.###.##..#..##..#.#.#.##.....#.#..#.#.#.#.###..#.#..#....###...######.##
+..#..######.##.#.#...#..##..#..##...#..#.####..#####.###.#..######...#.##
+....#..#.######...#.##.#...#.##.####..#.#.#.##.....##........#...####..##
+.##...#....#.###...##.##.#.##.#..##..##.#..##.....#..###.#...#.###.####.#
+..##..#......#.#...####.##.#.##.##.#.#.#.#.###.#..##..######.#...####..##
+#########.....#.###.#.###..#.#...#.####.##..##.##.###.###..##..#...##....
+...###.##..#.###.#.###.#...#..#..#.###.....#.##.#..#.#.#####.#..##.#.....
+##..##..#.#.....#....#....##...##..###..#.######.##.##...#..###.##..#####
+..#.#....###..###..#..#..#...#..###..#..####.#####..#..##...#..####.#.###
+.##.#.....##.##....#...#....##....##..#....#.#..##....####..###....#..#.#
+.#..#.#...#.#....##...#.#.#..###.#.#.###..#.##........#...#.##.#..##.#...
+#####.......#.##.#.##...#.##..#.#####....###..##.#..##.#....#...#..######
+#..#....#.#.#.###.###.#.....####.#..#..#..#.#.#..##..###.######..#####..#
+..###..#....##..##.####....#.#...#####.####.###.#.#.#..####.##.#..##.....
+.#.##.#.##...###...#...###.....#...#....#.##....#...#.#....##.##...#..##.
+.#..#.#..#..#..#.#..#.###.#####....###....##.#.####.##....#.#..#######..#
+###..#.##......##.#..##.....###...###.##...###..#####.#..#.####...#####..
+#.###.#.##.#..#....#....#.#.#######.###.##.#.##.###.#.##..##..#.#..#..##.
+..#.#...#..###....##.#.#...###########.#.#.#.##.#.##..##...####.##.##..#.
+.#....#.#.##...#........##.#..#.....#...#####.##.#.#.......#.####.##..#..
+###..###.##..##.###....#..###.#####.#..##.#.##.#....#.###.##.#.##....##..
+..###....####....####....#.....#....#####.#..#.#.##..###.#........#.....#
+#.#..#....#..#..#.#..#####..#..#....#.##.#.#.####..###.##..#.##..#.#.####
+.#...#...####...#...#.....#..#.######.#......#..#..#....#..#.####..##...#
+....##...##.#...#...#..#####.....#.#..#.#.###.#.#...##....#####.#.##.##.#
+###.###..#..##.#.#..###.#..###...######.#.#.###.#.####..#..###..#...####.
+###.#..#....#.#.#..###.....#####....#..#####...#.##..#....##.#.#.#.##..#.
+###...##...##.##.###..#...#..###.###.#######..###.##...##...####...##....
+.##.##.##.##.#.#...#.##.#...###.##.#######.#.###..#.##.#.#...#.####...#.#
+.#.###.....#######...##.#....##...##.#...#####.##....##..#.#.#..##....#.#
+..##....######.###.##.###..###..#.#.###..##.#.#########....#.......#...#.
+..#.####.#..#.##...#.##..#.###.....##.#.#.#...######.#.#.#######.###.#.#.
+##.###..#.###.#...#...#.#..#.##...#...#...##....##.#.##.#..#.....#.###.##
+#.##...#...#.#......#.#..#.#.....##..#..#..#####.#....##.#######..#...#..
+#.##..##..#..####.####.#...#......##.#..#..#.#..##..###.#.##..#...#.#....
+#.##..#.#.#####.#.#.####..#.####.#.#...##.#.#....#.#..#.#..####...####...
+####.#.#..##..#...###....###.##.#...##...#.#..##...#...#.#..#.##.#.#.#.##
+###.###.###.#.####..#.#.#..#.#...####..##.#.###..###..##....#..#...#.##.#
+##..#.#######.....#.##.#..####.####.#..#..#..#....#########.#.###.##....#
+..#......##.###..#..#.#..##....##.#...#.##.#.#.#..#.##.##..##.#.##.#.####
+###.##...#...#.#.#####.....#...####.##..###..###.##.#.###.##.##...####...
+.......#..#.###..##.#...#.#.#.....##.###.##.##..##.....#.#..######...##..
+.###.#.##...#.#..#####.##.#.##.#...#.###...#.#.##.####.#..##.####.#.#.###
+..#.##......#####.##..##.#.##.#.###....#.#...#.#....###.####...#.###..#..
+.#..#.#....#.##.....######.#.....#.##...###..#.###.#..##...####.###.#...#
+###.#.##.#..####..#.....###..#..####....####.###.###..######...##.......#
+##.#.#.#...#.###.##.#..##.#....#...#.####.#.#.######..####....##..#.#.#..
+....###.#....#.#.##....##.#.#...#...##...##..##.#####.####.#...#..#...#.#
+#####.###...####.###...###..##.......#...#.#.#.###.####......#.#.##..####
+#..##.#.######.####.######..##.#..###.....#.##.......#..##..#.....####...
+..##...####...##..##.#..###.#####..##...##.###.##..##..#.####..##.##.##..
+..#.#.#..##..#.##..#####...#.###.##.#.#....##...##.#..#....###.########.#
+.#..########...#.....###.###..#..#..#..#.####.#...##..#..#.#........####.
+.##...####.#.#.#.#.###.#.##...#..##...##.#.##..####....#..#.....#.###..#.
+#....#.##....##.#..##.#..#....#..##......##.#.#..###.#..#..#.#....#.###.#
+.#.####..##..#####............#..#.###...#...#.#.##.####.####.#..####.#..
+.....##..#.##.#....#...#####..##.....##........#..#.##.##..######..##..##
+.##.######..#.#....#...##.##.#..######.##.###.#.###.#.###..#...#####.####
+.###.###.#....##.##.#..#..####...#.#.....#..##..##...###.##..####.##..##.
+.#..#.##..##.#.#....#.####.##..#...#..###.#.####.###..#.#....#....#.###.#
+#.#..##..#.#.#..#.#.###..##...##..####...#.#..##...#.##....#..#.##..#...#
+######.##.#.##.###.#.#######.....###.#...#.###...##...##..#..###.#..####.
+..##..#.#..##......#..#.#....#..##..##.##..##.##.#.#..#.#...##....#.#####
+###.#.###..#.##...##..#######..#....###.#...##.#.###.##...##...##.##.##.#
+..#..##...#.##.###...#.#......#...##..##.#.####.#.....##..#.##..###.#.##.
+#.....#.##.#...###...##..###......##...##.###....#...##..##........#.##.#
+.#.##.#.#.#####..#.#.....##...#..##.##.##.#.##..##..##..#...#..#.....##..
+#.##.#.###.##..#..#.#.......###.##...##.#.#....##.#...#..##..##....#..#.#
+.##.##.#.##.##.###.###.##.###.#.#.#..#..........###..###...#.#......#..##
+###.#...##.#...##.#.#..##....##.####..###.##.#..#.#..###.#.#.#..##.#.#.#.
+.##.#..#...#.###..##.#...#....#...#..#########..####.#.#.........#....###";
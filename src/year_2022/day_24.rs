@@ -0,0 +1,221 @@
+use crate::{
+    bitset::BitSet,
+    math::lcm,
+    parse::{Input, ParseError},
+    search::bfs,
+    solver::Solver,
+};
+
+/// `(row, column)` inside the walls; the entrance sits at row -1 and the
+/// exit at row `height`.
+type Pos = (i32, i32);
+
+/// Every blizzard moves one tile a minute along its own row or column and
+/// wraps around, so each row and column is just bit patterns sliding in a
+/// cycle and nothing needs simulating.
+#[derive(Debug, Default)]
+struct Basin {
+    width: i32,
+    height: i32,
+    entrance: Pos,
+    exit: Pos,
+    // where each blizzard starts, per row for `>` and `<` and per column for
+    // `v` and `^`
+    right: Vec<BitSet>,
+    left: Vec<BitSet>,
+    down: Vec<BitSet>,
+    up: Vec<BitSet>,
+}
+
+impl Basin {
+    fn parse(input: Input) -> Result<Self, ParseError> {
+        let lines = input.lines().collect::<Vec<Input>>();
+        let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+            return Ok(Self::default());
+        };
+        let height = lines.len() as i32 - 2;
+        let width = first.as_str().len() as i32 - 2;
+        let gap = |line: &Input| match line.as_str().find('.') {
+            Some(col) => Ok(col as i32 - 1),
+            None => Err(line.error("wall has no opening")),
+        };
+
+        let mut basin = Self {
+            width,
+            height,
+            entrance: (-1, gap(first)?),
+            exit: (height, gap(last)?),
+            right: vec![BitSet::new(); height as usize],
+            left: vec![BitSet::new(); height as usize],
+            down: vec![BitSet::new(); width as usize],
+            up: vec![BitSet::new(); width as usize],
+        };
+        for (row, line) in lines[1..lines.len() - 1].iter().enumerate() {
+            for (col, c) in line.chars().enumerate().skip(1).take(width as usize) {
+                let col = col - 1;
+                match c {
+                    '>' => basin.right[row].insert(col),
+                    '<' => basin.left[row].insert(col),
+                    'v' => basin.down[col].insert(row),
+                    '^' => basin.up[col].insert(row),
+                    '.' => false,
+                    c => return Err(line.advance(col + 1).error(format!("unknown tile `{c}`"))),
+                };
+            }
+        }
+        Ok(basin)
+    }
+
+    /// After this many minutes every blizzard is back where it started.
+    fn period(&self) -> usize {
+        lcm(self.width as u64, self.height as u64) as usize
+    }
+
+    fn is_clear(&self, (row, col): Pos, minute: usize) -> bool {
+        if (row, col) == self.entrance || (row, col) == self.exit {
+            return true;
+        }
+        if !(0..self.height).contains(&row) || !(0..self.width).contains(&col) {
+            return false;
+        }
+
+        let t = minute as i32;
+        let (r, c) = (row as usize, col as usize);
+        !(self.right[r].contains((col - t).rem_euclid(self.width) as usize)
+            || self.left[r].contains((col + t).rem_euclid(self.width) as usize)
+            || self.down[c].contains((row - t).rem_euclid(self.height) as usize)
+            || self.up[c].contains((row + t).rem_euclid(self.height) as usize))
+    }
+
+    /// How long it takes to get from `from` to `to`, setting off at `start`.
+    fn crossing(&self, from: Pos, to: Pos, start: usize) -> Option<usize> {
+        if self.width <= 0 || self.height <= 0 {
+            return None;
+        }
+
+        let period = self.period();
+        let successors = |&(pos, phase): &(Pos, usize)| {
+            let next = (phase + 1) % period;
+            [(0, 0), (0, 1), (1, 0), (0, -1), (-1, 0)]
+                .into_iter()
+                .map(move |(dr, dc)| (pos.0 + dr, pos.1 + dc))
+                .filter(move |p| self.is_clear(*p, next))
+                .map(move |p| (p, next))
+        };
+        bfs([(from, start % period)], successors, |(pos, _)| *pos == to).map(|(_, minutes)| minutes)
+    }
+
+    /// The minute the last stop of `trip` is reached, travelling as fast as
+    /// possible.
+    fn trip(&self, trip: &[Pos]) -> Option<usize> {
+        trip.windows(2).try_fold(0, |minute, leg| {
+            self.crossing(leg[0], leg[1], minute)
+                .map(|minutes| minute + minutes)
+        })
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Solution {
+    basin: Basin,
+}
+
+impl Solver for Solution {
+    fn get_input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn with_input(&mut self, input: &str) {
+        self.basin = Basin::parse(Input::new(input)).unwrap();
+    }
+
+    fn solve_part1(&self) -> String {
+        let basin = &self.basin;
+        basin
+            .trip(&[basin.entrance, basin.exit])
+            .map_or("no path".to_string(), |t| t.to_string())
+    }
+
+    fn solve_part2(&self) -> String {
+        let basin = &self.basin;
+        basin
+            .trip(&[basin.entrance, basin.exit, basin.entrance, basin.exit])
+            .map_or("no path".to_string(), |t| t.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_example_input<'a>() -> &'a str {
+        "\
+#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#"
+    }
+
+    #[test]
+    fn test_solution_example1() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "18");
+    }
+
+    #[test]
+    fn test_solution_example2() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "54");
+    }
+
+    #[test]
+    fn test_solution_part1() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "192");
+    }
+
+    #[test]
+    fn test_solution_part2() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "615");
+    }
+}
+
+const INPUT: &str = "\
+#.########################################################################################################################
+#><<<v.<v^>.v<<<^.v.^v.<.>>><>vv.>.v<.<v>v<.^.<.v<<<.^.>>.v^vv>v<v><^.<v^v.v.>.>..<.>v.^^<v>>^>>v..>>.<^<>v<<..v..v..>^>>#
+#<vv<...>v<^^<..^<.vv<.<^<v.<<>..>^v.<><<..<.>>v.<^^^<^v>....v^.<.v^>>^>^<.<><<<v>v<.v..<v.>.>>^<v>>^.>.>.<.<^.>>v..^..<<#
+#.^<v>v>v.>.>^<<v<.vvv>..<..v<>v<><.^^.>vvvv<.<>.v^.....<^.v^v<>v^<..vv.^..v>..v>vv...<<.v^.v>^^.<.v<.>.v<<<v<...<<v..<<>#
+#<v.vv.^.>^>.>.<..^vv^v<^.>>.^>.^^..><.v<.v...>v.<v>.>.^v.v.^>^<v..>.^>^>.>.<v^^<^<^.v^.>^>^v<v^<^v>v<..v>>..v>..^..>v^..#
+#<....>.v<^<.<>.>v>.^^<.>^...><..>.>^.>.>vv...<v<v>v.<vvv<>^...^<.><.^<^><>v..^.^.<^vv>vv.>.^^v>v>v>.v<..>><<.^^<.<..>^<<#
+#<v..><v><...<.><><>>.>v.<..<<vvv.v.>>^>^>^>^>.^<vv^<>^<^..^>v.><>^><>^>>.<vv<<>..<>><v.^^.v<v<>.vvv^.>.>v...<>^v>>..^>.<#
+#.vv<>>v.<<^<>^..v..^.>>><><.v.^<^v<<v^.v<>>^^.<<>>v^vv>>.v.>.v^vv....>^.^^vvv.<<^.^v^>.>v>.>.^<^.<.<^^^...v^v.>><.v^>^.<#
+#<.<vv^<^.<.^<v<vv^.v<v<v<..>.^^.<v^<.<.^.^>v<v^^<.<^.>^<^^^.v.^^.>.>^>.v.><>.<<.....>>v^>vv.v..v^><>.^^>><v.^^vvv^>.>...#
+#..<vv^>>^v>>>v..^<>^..^v><v^^^.<<>><v>><.v^>.<><<<^.^...<<v..>>.<.v.^.^..^vv<>>.<<^^..>v^^v.<>...<^^.<..^^.>>.<.vv.^^v..#
+#.<^vv.^v>.<>..v^>>..>^^v^><>v^>>...<>.v^<^^<v.v<<v^<.<<.>>.^vv^>>>^^.v.^.<<<...vv.v.vv.^^v..<>.v>vv^.>><.v><.v.<^.<....>#
+#.v^v^<<<>>..^......v.<vv>>.v....^.<<^<<...<.<.<<^<<>>^v^^v..^.v.^>v.vvv...^^>.<.<>>.>^<..>v<.><>^^.>>..>....^..^>^^>>^v.#
+#<v^<<<<.>>vvv^><..^>^^<..<.<<v>.<^.<^v><.<^<v>.vv.^<v>^.v^v..>^>>v.^vv>^<.^^v^<<..^...v<^v^^..v.^.><.<vv^><v.<..<<..>v.>#
+#>^..^vv>^<>.<><.<.v<<^<>v.^^^><^^^>.<^vv^^<^>v<<.<..<.^<<.>v^<^>.v^.<.v....^<.>v<^^v.<v^>.^..<.vv^<<vv.^.v^><^^^>.<<>^v<#
+#..^.v^v.^.<<>>.<>.<v^>.^.><v.vv...<><^<v<<.>.<^v.^<v>.^<>...^>..^>>.v<.<v.^v><>v^>><..<v>><<.^v<.><.v<>.v>^..>.<v><v<<<.#
+#.<..>.>><>^<<.v^>.^.^v<v.<<>.<vv..<v^v..^<<^^^.v..<..>^^.<>^>.^<vv>v.^v^.^v^^.^^..^<<.^>.>.<><..<..^>>>>v...v..v^vv>^<^.#
+#<^<.v^><v.<<.^>.<.^<v><..>.>>^..<vv.>v^<><..><.<^^^v<^><vv<..vvv.><.vv<>v>^>><<vv.v<.v>>>vv.>^<v>vv.vv..^^...><>>>...v.<#
+#<.^^>^^^v>vvv<<.<>>v^>^<vv..vv>>>.^<<<.>.>^>.><.<v<.^v<<<><.<....<><..>>..^>>^v^.v..<>..>>^>^.<.><vvv^<<v><^.^^v.^.<<v.<#
+#.v.v<<.>.>>>^^>v.v>.<.>..v<^..>>^.>v.^.v<>v<v>>^<>.>..<><v....>.^v.>>^...<.<.<.^.v>>...><<vv.<><.<v^><v..v....v>^v.<<..<#
+#><<v.vv^><^.>.>.v>v>.^.><v<v<<.>>^.>^<..<vvvv^.>.<^>..<.>.<^vv>>..^v.<<..>..>..>>.>..^v<vv^..>v>..^...>.v..^>>.^v.vv^>v<#
+#<>v<>.><^v^<.^^<v^<v>.v.><v.<.>.<v..^v^^<>v<^..<>^>..>.><>^.v^^.>v^<<^v>.>^<<..^vv>><vv^<vv><v.v.v><>.^<..v.<<>^....v>v>#
+#..vv..^.<v>.<>.v^^<>><v>^<...>.>v>...^><v^..<v^<>>><>v>.<^vv..v^..v^v.>v>.>.><.v<.><<v.<<.^..<>^v<><>..<..v<v.v.^>v<.vv>#
+#<v>v<<<v<<>.><<<<^^vv^<<<v<.^>v.>.^^.>>^^<.<>..<..<>v.^..<.>v^v>v..<>^^..^<v<....<^.vv...<^^.v^.>v<>..^v<^^v.^..>.>^..>>#
+#>.v<..^.<vv<^.<...>^>.v.^^..v.>>.v^>.><^v.<...>.<.^>><^.<>>..<v.vv>..v.<^..v^.>.><.>v.>^>.v...^.v><..<>>>..v<v.>v.><v>^>#
+#>>..^<>>.^<<.<<>>v<^.>>.v><v><^>vvvv^^v<v<<>^v^v>v.<.v>^<^v^v<.^^<v.>vv<>v<.v>v<.^v^vv<>.>v.<><<..^<>.<>><<..>><v<^<v^^.#
+#.^<..v..>^..v^<^v^v>.^v..>v^^.<.>.<v<^^<v^^.<<<..v....^.vv<><.v^>v>.<.^<><.>..>>v.<><<>..^.>v^^>.v<vv^v^vv<>.>^>>>^.><><#
+########################################################################################################################.#";
@@ -0,0 +1,428 @@
+use std::{
+    fmt::Display,
+    iter::Sum,
+    ops::{Add, AddAssign},
+    str::FromStr,
+};
+
+use crate::{
+    parse::{Input, ParseError},
+    solver::Solver,
+};
+
+/// A number written in balanced base five, with digits `=` (-2), `-` (-1),
+/// `0`, `1` and `2`. Any integer can be written this way, negative ones
+/// included, and the digits can grow past what fits in an `i128`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Snafu {
+    // least significant first, with no trailing zeros; empty for zero
+    digits: Vec<i8>,
+}
+
+impl Snafu {
+    pub fn zero() -> Self {
+        Self { digits: Vec::new() }
+    }
+
+    fn normalized(mut digits: Vec<i8>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self { digits }
+    }
+
+    pub fn parse(input: Input) -> Result<Self, ParseError> {
+        if input.is_empty() {
+            return Err(input.error("empty SNAFU number"));
+        }
+
+        let digits = input
+            .chars()
+            .enumerate()
+            .map(|(i, c)| match c {
+                '=' => Ok(-2),
+                '-' => Ok(-1),
+                '0' => Ok(0),
+                '1' => Ok(1),
+                '2' => Ok(2),
+                c => Err(input.advance(i).error(format!("invalid SNAFU digit `{c}`"))),
+            })
+            .collect::<Result<Vec<i8>, _>>()?;
+        Ok(Self::normalized(digits.into_iter().rev().collect()))
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        // partial sums can overflow even when the whole number fits, so
+        // wrap and then check the result converts back
+        let value = self
+            .digits
+            .iter()
+            .rev()
+            .fold(0i128, |acc, d| acc.wrapping_mul(5).wrapping_add(*d as i128));
+        (Snafu::from(value) == *self).then_some(value)
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        self.to_i128()?.try_into().ok()
+    }
+}
+
+impl Default for Snafu {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl From<i128> for Snafu {
+    fn from(mut value: i128) -> Self {
+        let mut digits = Vec::new();
+        while value != 0 {
+            // div_euclid first so that i128::MIN can't overflow
+            let (mut quotient, mut digit) = (value.div_euclid(5), value.rem_euclid(5));
+            if digit > 2 {
+                digit -= 5;
+                quotient += 1;
+            }
+            digits.push(digit as i8);
+            value = quotient;
+        }
+        Self { digits }
+    }
+}
+
+impl From<i64> for Snafu {
+    fn from(value: i64) -> Self {
+        Self::from(value as i128)
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(Input::new(s))
+    }
+}
+
+impl Display for Snafu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+        for d in self.digits.iter().rev() {
+            let c = match d {
+                -2 => '=',
+                -1 => '-',
+                0 => '0',
+                1 => '1',
+                _ => '2',
+            };
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Add for &Snafu {
+    type Output = Snafu;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let len = self.digits.len().max(rhs.digits.len());
+        let mut digits = Vec::with_capacity(len + 1);
+        let mut carry = 0;
+        for i in 0..len {
+            let a = self.digits.get(i).copied().unwrap_or(0);
+            let b = rhs.digits.get(i).copied().unwrap_or(0);
+            let sum = a + b + carry;
+            let (next_carry, digit) = match sum {
+                3.. => (1, sum - 5),
+                ..-2 => (-1, sum + 5),
+                _ => (0, sum),
+            };
+            digits.push(digit);
+            carry = next_carry;
+        }
+        digits.push(carry);
+        Snafu::normalized(digits)
+    }
+}
+
+impl Add for Snafu {
+    type Output = Snafu;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl AddAssign<&Snafu> for Snafu {
+    fn add_assign(&mut self, rhs: &Snafu) {
+        *self = &*self + rhs;
+    }
+}
+
+impl<'a> Sum<&'a Snafu> for Snafu {
+    fn sum<I: Iterator<Item = &'a Snafu>>(iter: I) -> Self {
+        iter.fold(Snafu::zero(), |acc, n| &acc + n)
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Solution {
+    input: Vec<Snafu>,
+}
+
+impl Solver for Solution {
+    fn get_input(&self) -> &'static str {
+        INPUT
+    }
+
+    fn with_input(&mut self, input: &str) {
+        self.input = Input::new(input)
+            .lines()
+            .map(Snafu::parse)
+            .collect::<Result<_, _>>()
+            .unwrap();
+    }
+
+    fn solve_part1(&self) -> String {
+        self.input.iter().sum::<Snafu>().to_string()
+    }
+
+    fn solve_part2(&self) -> String {
+        "".to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_example_input<'a>() -> &'a str {
+        "\
+1=-0-2
+12111
+2=0=
+21
+2=01
+111
+20012
+112
+1=-1=
+1-12
+12
+1=
+122"
+    }
+
+    // xorshift, so the property tests cover a spread of values without
+    // pulling in a random number crate
+    fn samples(count: usize) -> impl Iterator<Item = i64> {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let edges = [0, 1, -1, 2, -2, 3, i64::MAX, i64::MIN, i64::MAX - 2];
+        edges.into_iter().chain((0..count).map(move |_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            // vary the magnitude as well as the digits
+            (state as i64) >> (state % 64)
+        }))
+    }
+
+    #[test]
+    fn test_solution_example1() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "2=-1=0");
+    }
+
+    #[test]
+    fn test_solution_example2() {
+        let mut solver = Solution::default();
+        solver.with_input(get_example_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "");
+    }
+
+    #[test]
+    fn test_solution_part1() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part1();
+        assert_eq!(solution, "2011====-=201111022==");
+    }
+
+    #[test]
+    fn test_solution_part2() {
+        let mut solver = Solution::default();
+        solver.with_input(solver.get_input());
+        let solution = solver.solve_part2();
+        assert_eq!(solution, "");
+    }
+
+    #[test]
+    fn test_known_values() {
+        for (n, s) in [
+            (0, "0"),
+            (3, "1="),
+            (8, "2="),
+            (-8, "=2"),
+            (2022, "1=11-2"),
+            (314159265, "1121-1110-1=0"),
+        ] {
+            assert_eq!(Snafu::from(n).to_string(), s);
+            assert_eq!(s.parse::<Snafu>().unwrap().to_i64(), Some(n));
+        }
+        assert!("1=3".parse::<Snafu>().is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for n in samples(10_000) {
+            let snafu = Snafu::from(n);
+            assert_eq!(snafu.to_i64(), Some(n));
+            assert_eq!(snafu.to_string().parse::<Snafu>(), Ok(snafu));
+        }
+        for n in [i128::MAX, i128::MIN] {
+            assert_eq!(Snafu::from(n).to_i128(), Some(n));
+        }
+    }
+
+    #[test]
+    fn test_addition() {
+        let values = samples(2_000).collect::<Vec<i64>>();
+        for pair in values.windows(2) {
+            let sum = Snafu::from(pair[0]) + Snafu::from(pair[1]);
+            assert_eq!(sum.to_i128(), Some(pair[0] as i128 + pair[1] as i128));
+        }
+
+        // sums past i128 still add up digit by digit
+        let max = Snafu::from(i128::MAX);
+        let doubled = &max + &max;
+        assert_eq!(doubled.to_i128(), None);
+        assert_eq!(&doubled + &Snafu::from(-i128::MAX), max);
+    }
+}
+
+const INPUT: &str = "\
+1010-=0-=0-
+2--21
+210=221=2==0-01
+110-2=0-=2012-012
+1-012=0=1-00
+2-0-2=1
+2-=
+20=1-0122-000==0-012
+2012101-12
+1-21=11=1-2-2-21
+11=22=1-11012
+1020=
+110-==
+210-111222==
+1=2-21=02=--2-===11
+210=-==1-=0
+2-0=0-2=12=-=21-12
+2-2020-==1-1
+1=--1
+222==
+201==20==1--02-0=-=
+121220211211=--102
+12-0=1-=211-=020-02
+2-12211211--=
+2=2=01-=120=21
+2-=-012-=000
+20=-2-000
+2-1=0210=
+1=--120=-=-
+2=1-0=00-10=2=
+1-==0212=0=1
+111-21
+11-20--1
+1=-=-220-112
+20-0-=1=2=102-
+2=-012
+20=20-12==-=
+11002002-2110
+10=112122
+22=10=--221=0=1=-=2
+21
+2-1-=0==0
+10--
+2-02220=11-
+1-122
+1==2121-0=-==0
+1=202==0
+1=2=--==
+120==0212-2--=
+2
+2=
+1--=0--
+20012-=--=2121
+21
+12-00110=1-1=-1-01==
+2
+20=1==-202-221-2=1==
+12221=0-=1102-12-11
+2-1=010=12=0
+22-1012021
+201--200=0020-20-2
+102-1-
+201-1202=2=-11=2-21
+10=-0=2--20-20-
+12=2=-12--=001=0
+121=-=22=21-
+121=--10-0012==
+20
+1
+2
+1=-0=
+2101-112=
+22-=10
+1220021
+1021-0--0==
+10=0-=00=-=02--200
+2=2-=0-=2=-102
+1=0222221-100-
+20=--1201-0--12=02
+1102-2--0=-=1=2=
+1-2
+2=-=0010
+2=1=0201-0
+10121=0==-=2-2112-
+1-001211-1-0--==0
+21=2111012-0
+22
+221100=2-01
+1-11
+22=-11-=01-=2
+2-0-
+2---0=000-1-21--0=1
+2201101222001-220-
+102
+11---1==2-1--
+102--20-0-221
+221=1-
+20=2==201-20111-=111
+102=-01=2=2=20-
+20=101=-20
+11=
+2-11
+102201-=-00
+1222201=
+1=000
+200=0-=1202==20-=
+20020=02=10=
+22=12211-
+11
+2
+2=-2-10=-02-12=12-
+11=20220=-
+21-1-=-=
+2==1-1-0-=2-2
+1-02-0-0=
+2-1=0
+2
+1001-0-0-10=1
+111==01201--=01
+1-211=-20-11120=2=1";